@@ -0,0 +1,164 @@
+use crate::export::ItemKind;
+use crate::report::{Category, Finding};
+use crate::tree::Tree;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Bumped whenever the layout of the baseline file changes
+pub const BASELINE_VERSION: u32 = 1;
+
+/// Findings that are already known about and shouldn't be reported again. Entries are keyed by
+/// category, item kind and path rather than location, so they survive unrelated edits.
+#[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Baseline {
+    pub version: u32,
+    #[serde(default)]
+    pub unused: BTreeMap<ItemKind, Tree<String>>,
+    #[serde(default)]
+    pub test_only: BTreeMap<ItemKind, Tree<String>>,
+    #[serde(default)]
+    pub over_exposed: BTreeMap<ItemKind, Tree<String>>,
+}
+
+/// A single finding recorded in a baseline
+#[derive(Clone, Debug, PartialEq)]
+pub struct BaselineEntry {
+    pub category: Category,
+    pub kind: ItemKind,
+    pub path: Vec<String>,
+}
+
+impl Baseline {
+    /// Records every finding whose kind is known
+    pub fn from_findings(findings: &[Finding<'_>]) -> Self {
+        let mut baseline = Self {
+            version: BASELINE_VERSION,
+            ..Self::default()
+        };
+
+        for finding in findings {
+            if let Some(export) = finding.export {
+                baseline
+                    .trees_mut(finding.category)
+                    .entry(export.kind)
+                    .or_insert_with(Tree::new)
+                    .insert_path(&finding.path, None);
+            }
+        }
+
+        baseline
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read baseline `{}`", path.display()))?;
+        let baseline: Self = serde_json::from_str(&contents)
+            .with_context(|| format!("failed to parse baseline `{}`", path.display()))?;
+
+        anyhow::ensure!(
+            baseline.version == BASELINE_VERSION,
+            "baseline `{}` has version {}, expected {}",
+            path.display(),
+            baseline.version,
+            BASELINE_VERSION
+        );
+
+        Ok(baseline)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let contents = serde_json::to_string_pretty(self)?;
+
+        std::fs::write(path, contents + "\n")
+            .with_context(|| format!("failed to write baseline `{}`", path.display()))
+    }
+
+    pub fn contains(&self, category: Category, kind: ItemKind, path: &[String]) -> bool {
+        self.trees(category)
+            .get(&kind)
+            .is_some_and(|tree| matches!(tree.get(path), Some(None)))
+    }
+
+    /// Every entry, ordered by category, kind and then path
+    pub fn entries(&self) -> Vec<BaselineEntry> {
+        [Category::Unused, Category::TestOnly, Category::OverExposed]
+            .into_iter()
+            .flat_map(|category| {
+                self.trees(category).iter().flat_map(move |(kind, tree)| {
+                    tree.leaves().into_iter().map(move |path| BaselineEntry {
+                        category,
+                        kind: *kind,
+                        path,
+                    })
+                })
+            })
+            .collect()
+    }
+
+    fn trees(&self, category: Category) -> &BTreeMap<ItemKind, Tree<String>> {
+        match category {
+            Category::Unused => &self.unused,
+            Category::TestOnly => &self.test_only,
+            Category::OverExposed => &self.over_exposed,
+        }
+    }
+
+    fn trees_mut(&mut self, category: Category) -> &mut BTreeMap<ItemKind, Tree<String>> {
+        match category {
+            Category::Unused => &mut self.unused,
+            Category::TestOnly => &mut self.test_only,
+            Category::OverExposed => &mut self.over_exposed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::export::Export;
+    use std::path::PathBuf;
+
+    fn export(kind: ItemKind) -> Export {
+        Export {
+            kind,
+            visibility: "pub".to_string(),
+            file: PathBuf::from("src/lib.rs"),
+            line: 1,
+            column: 1,
+            used_locally: false,
+            extent: None,
+            module_file: None,
+        }
+    }
+
+    #[test]
+    fn it_matches_entries_by_kind_and_path_but_not_location() {
+        let path = vec!["package_1".to_string(), "hello".to_string()];
+        let recorded = export(ItemKind::Fn);
+
+        let baseline = Baseline::from_findings(&[Finding {
+            category: Category::Unused,
+            path: path.clone(),
+            export: Some(&recorded),
+        }]);
+
+        assert!(baseline.contains(Category::Unused, ItemKind::Fn, &path));
+        assert!(!baseline.contains(Category::Unused, ItemKind::Struct, &path));
+        assert!(!baseline.contains(Category::TestOnly, ItemKind::Fn, &path));
+
+        let contents = serde_json::to_value(&baseline).unwrap();
+        let read_back: Baseline = serde_json::from_value(contents).unwrap();
+
+        assert_eq!(read_back, baseline);
+        assert_eq!(
+            read_back.entries(),
+            vec![BaselineEntry {
+                category: Category::Unused,
+                kind: ItemKind::Fn,
+                path,
+            }]
+        );
+    }
+}
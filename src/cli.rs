@@ -0,0 +1,231 @@
+use clap::{Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+
+/// Cargo passes the subcommand name through as the first argument, so the binary is parsed as
+/// `cargo purge ...`
+#[derive(Debug, Parser)]
+#[command(name = "cargo", bin_name = "cargo")]
+pub enum Cargo {
+    Purge(Args),
+}
+
+/// Find exports of workspace packages that nothing in the workspace uses
+#[derive(Debug, clap::Args)]
+#[command(version)]
+pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Path to Cargo.toml of the workspace to analyze
+    #[arg(long, value_name = "PATH", global = true)]
+    pub manifest_path: Option<PathBuf>,
+
+    /// Another workspace whose usage of this workspace's exports should be counted
+    #[arg(long = "workspace", value_name = "PATH", global = true)]
+    pub workspaces: Vec<PathBuf>,
+
+    /// Only report exports of these packages
+    #[arg(short, long = "package", value_name = "SPEC", global = true)]
+    pub packages: Vec<String>,
+
+    /// Don't report exports of these packages
+    #[arg(long, value_name = "SPEC", global = true)]
+    pub exclude: Vec<String>,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = Format::Text, global = true)]
+    pub format: Format,
+
+    /// Only count the names referenced in the importing module as used by a glob import
+    #[arg(long, global = true)]
+    pub precise_globs: bool,
+
+    /// Only count exports as used when a binary, test or other root reaches them
+    #[arg(long, global = true)]
+    pub reachability: bool,
+
+    /// Also report `pub(crate)`, `pub(super)` and `pub(in path)` items nothing in their crate uses
+    #[arg(long, global = true)]
+    pub intra_crate: bool,
+
+    /// Only print findings
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
+    /// Coloring of text output
+    #[arg(
+        long,
+        value_enum,
+        value_name = "WHEN",
+        default_value_t = Color::Auto,
+        global = true
+    )]
+    pub color: Color,
+
+    /// Exit with code 1 when there are findings
+    #[arg(long)]
+    pub deny: bool,
+
+    /// Don't report findings recorded in this baseline file
+    #[arg(long, value_name = "PATH")]
+    pub baseline: Option<PathBuf>,
+
+    /// Record the current findings in a baseline file instead of reporting them
+    #[arg(long, value_name = "PATH", conflicts_with = "baseline")]
+    pub write_baseline: Option<PathBuf>,
+
+    /// Narrow the visibility of unused exports in place, or remove them
+    #[arg(
+        long,
+        value_enum,
+        value_name = "MODE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "narrow"
+    )]
+    pub fix: Option<FixMode>,
+
+    /// With --fix, print a diff instead of editing files
+    #[arg(long, requires = "fix")]
+    pub dry_run: bool,
+
+    /// With --fix, edit files even if the git working tree has uncommitted changes
+    #[arg(long, requires = "fix")]
+    pub allow_dirty: bool,
+
+    /// With --deny, tolerate up to N findings per package
+    #[arg(long, value_name = "N", requires = "deny")]
+    pub max_unused: Option<usize>,
+}
+
+#[derive(Debug, PartialEq, Subcommand)]
+pub enum Command {
+    /// List every export with the number of references to it and where they are
+    Usages,
+    /// Explain how an item is exported and referred to, and why it is or isn't reported
+    Why {
+        /// Absolute path of the item, such as `my_crate::module::Item`
+        path: String,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum Format {
+    Text,
+    Json,
+    Sarif,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum FixMode {
+    /// Narrow `pub` to `pub(crate)`, or drop it for items only their own module uses
+    Narrow,
+    /// Delete unused items, rolling back any removal that breaks `cargo check`
+    Remove,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum Color {
+    Auto,
+    Always,
+    Never,
+}
+
+/// Which packages' exports end up in the report. Imports are always collected from every member.
+#[derive(Clone, Debug, Default)]
+pub struct PackageFilter {
+    pub packages: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl PackageFilter {
+    pub fn includes(&self, package: &str) -> bool {
+        (self.packages.is_empty() || self.packages.iter().any(|spec| spec == package))
+            && !self.exclude.iter().any(|spec| spec == package)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_parses_cargo_subcommand_arguments() {
+        let Cargo::Purge(args) = Cargo::parse_from([
+            "cargo",
+            "purge",
+            "--workspace",
+            "../other",
+            "--workspace",
+            "../another",
+            "-p",
+            "package_1",
+            "--format",
+            "json",
+        ]);
+
+        assert_eq!(
+            args.workspaces,
+            vec![PathBuf::from("../other"), PathBuf::from("../another")]
+        );
+        assert_eq!(args.packages, vec!["package_1".to_string()]);
+        assert_eq!(args.format, Format::Json);
+        assert_eq!(args.color, Color::Auto);
+    }
+
+    #[test]
+    fn it_filters_packages() {
+        let filter = PackageFilter {
+            packages: vec!["package_1".to_string(), "package_2".to_string()],
+            exclude: vec!["package_2".to_string()],
+        };
+
+        assert!(filter.includes("package_1"));
+        assert!(!filter.includes("package_2"));
+        assert!(!filter.includes("package_3"));
+        assert!(PackageFilter::default().includes("package_3"));
+    }
+
+    #[test]
+    fn it_requires_deny_for_a_threshold() {
+        let result = Cargo::try_parse_from(["cargo", "purge", "--max-unused", "3"]);
+        assert!(result.is_err());
+
+        let Cargo::Purge(args) =
+            Cargo::parse_from(["cargo", "purge", "--deny", "--max-unused", "3"]);
+        assert!(args.deny);
+        assert_eq!(args.max_unused, Some(3));
+    }
+
+    #[test]
+    fn it_defaults_to_narrowing_when_fixing() {
+        let Cargo::Purge(args) = Cargo::parse_from(["cargo", "purge", "--fix", "--dry-run"]);
+        assert_eq!(args.fix, Some(FixMode::Narrow));
+
+        let Cargo::Purge(args) = Cargo::parse_from(["cargo", "purge", "--fix=remove"]);
+        assert_eq!(args.fix, Some(FixMode::Remove));
+    }
+
+    #[test]
+    fn it_accepts_options_after_a_subcommand() {
+        let Cargo::Purge(args) =
+            Cargo::parse_from(["cargo", "purge", "usages", "-p", "package_1", "--quiet"]);
+
+        assert_eq!(args.command, Some(Command::Usages));
+        assert_eq!(args.packages, vec!["package_1".to_string()]);
+        assert!(args.quiet);
+    }
+
+    #[test]
+    fn it_parses_the_path_to_explain() {
+        let Cargo::Purge(args) =
+            Cargo::parse_from(["cargo", "purge", "why", "package_1::public_module::hello"]);
+
+        assert_eq!(
+            args.command,
+            Some(Command::Why {
+                path: "package_1::public_module::hello".to_string()
+            })
+        );
+    }
+}
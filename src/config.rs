@@ -0,0 +1,316 @@
+use crate::cli::PackageFilter;
+use crate::report::Finding;
+use crate::visitor::GlobMode;
+use crate::{existing_path, manifest_path};
+use anyhow::{Context, Result};
+use cargo::core::Workspace;
+use glob::Pattern;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Name of the configuration file looked up in the workspace root
+pub const CONFIG_FILE: &str = "purge.toml";
+
+/// Contents of `purge.toml` and of `[workspace.metadata.purge]`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct WorkspaceConfigFile {
+    /// Item paths or glob patterns, e.g. `my_crate::internal::*`
+    pub ignore: Vec<String>,
+    /// Glob patterns of files relative to the workspace root
+    pub ignore_files: Vec<String>,
+    /// Other workspaces whose usages count, relative to the workspace root
+    pub workspaces: Vec<PathBuf>,
+    /// Packages that are used outside the analyzed workspaces
+    pub public_packages: Vec<String>,
+    /// Severity of the findings of each package, by package name
+    pub severity: BTreeMap<String, Severity>,
+    pub precise_globs: bool,
+    pub reachability: bool,
+    /// Item paths or glob patterns of items that are used from outside the analyzed workspaces
+    pub roots: Vec<String>,
+    pub intra_crate: bool,
+}
+
+/// Contents of `[package.metadata.purge]`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct PackageConfigFile {
+    /// Item paths or glob patterns, e.g. `my_crate::internal::*`
+    pub ignore: Vec<String>,
+    /// Glob patterns of files relative to the package root
+    pub ignore_files: Vec<String>,
+    /// Whether the package is used outside the analyzed workspaces
+    pub public: bool,
+    pub severity: Option<Severity>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// Findings count towards `--deny`
+    #[default]
+    Deny,
+    /// Findings are reported but never fail `--deny`
+    Warn,
+}
+
+/// Everything deciding what is analyzed and what ends up in the report
+#[derive(Debug)]
+pub struct PurgeConfig {
+    /// Workspace whose exports are reported
+    pub workspace: PathBuf,
+    /// Other workspaces whose usages of the exports count
+    pub workspaces: Vec<PathBuf>,
+    pub glob_mode: GlobMode,
+    pub filter: PackageFilter,
+    /// Item paths that are never reported
+    pub ignore: Vec<Pattern>,
+    /// Files, relative to the workspace root, whose exports are never reported
+    pub ignore_files: Vec<Pattern>,
+    /// Severity of the findings of each package, by crate name
+    pub severity: BTreeMap<String, Severity>,
+    /// Name of the library of each member package, which the paths of its exports start with.
+    /// It only differs from the package name when `[lib] name` is set.
+    pub lib_names: BTreeMap<String, String>,
+    /// Whether exports only count as used when reachable from a root
+    pub reachability: bool,
+    /// Items that reachability starts from, besides binaries, tests and `#[no_mangle]` items
+    pub roots: Vec<Pattern>,
+    /// Whether items with a restricted visibility such as `pub(crate)` are reported as well
+    pub intra_crate: bool,
+}
+
+impl PurgeConfig {
+    pub fn new(workspace: PathBuf) -> Self {
+        Self {
+            workspace,
+            workspaces: Vec::new(),
+            glob_mode: GlobMode::Conservative,
+            filter: PackageFilter::default(),
+            ignore: Vec::new(),
+            ignore_files: Vec::new(),
+            severity: BTreeMap::new(),
+            lib_names: BTreeMap::new(),
+            reachability: false,
+            roots: Vec::new(),
+            intra_crate: false,
+        }
+    }
+
+    /// Reads `purge.toml` and `[workspace.metadata.purge]` from the workspace root, and
+    /// `[package.metadata.purge]` from every member
+    pub fn load(workspace: PathBuf) -> Result<Self> {
+        let mut config = Self::new(workspace);
+
+        let manifest_path = manifest_path(&config.workspace);
+        let cargo_config = cargo::Config::default()?;
+        let cargo_workspace = Workspace::new(&manifest_path, &cargo_config)
+            .with_context(|| format!("failed to load workspace `{}`", manifest_path.display()))?;
+        let root = cargo_workspace.root();
+
+        // `--manifest-path` may point at the manifest, or at a member rather than the workspace
+        config.workspace = root.into();
+
+        // Settings are keyed by package, findings by the crate name of the package's library
+        for package in cargo_workspace.members() {
+            if let Some(lib) = package.targets().iter().find(|target| target.is_lib()) {
+                config
+                    .lib_names
+                    .insert(package.name().to_string(), lib.crate_name());
+            }
+        }
+
+        let config_path = root.join(CONFIG_FILE);
+        if config_path.exists() {
+            let contents = std::fs::read_to_string(&config_path)
+                .with_context(|| format!("failed to read `{}`", config_path.display()))?;
+            let file = toml::from_str(&contents)
+                .with_context(|| format!("failed to parse `{}`", config_path.display()))?;
+
+            config.add_workspace_config(root, file)?;
+        }
+
+        if let Some(file) = read_metadata(&manifest_path, "workspace")? {
+            config.add_workspace_config(root, file)?;
+        }
+
+        for package in cargo_workspace.members() {
+            let Some(file) =
+                read_metadata::<PackageConfigFile>(package.manifest_path(), "package")?
+            else {
+                continue;
+            };
+
+            let package_dir = package.root().strip_prefix(root).unwrap_or(Path::new(""));
+
+            config.ignore.extend(parse_patterns(&file.ignore)?);
+            config.ignore_files.extend(parse_patterns(
+                file.ignore_files
+                    .iter()
+                    .map(|pattern| package_dir.join(pattern).to_string_lossy().into_owned()),
+            )?);
+
+            if file.public {
+                config.filter.exclude.push(package.name().to_string());
+            }
+
+            if let Some(severity) = file.severity {
+                let lib_name = config.lib_name(&package.name());
+                config.severity.insert(lib_name, severity);
+            }
+        }
+
+        Ok(config)
+    }
+
+    fn add_workspace_config(&mut self, root: &Path, file: WorkspaceConfigFile) -> Result<()> {
+        self.ignore.extend(parse_patterns(&file.ignore)?);
+        self.ignore_files
+            .extend(parse_patterns(&file.ignore_files)?);
+
+        for path in file.workspaces {
+            self.workspaces.push(existing_path(root, &path)?);
+        }
+
+        self.filter.exclude.extend(file.public_packages);
+
+        for (package, severity) in file.severity {
+            self.severity.insert(self.lib_name(&package), severity);
+        }
+
+        if file.precise_globs {
+            self.glob_mode = GlobMode::Precise;
+        }
+
+        self.reachability |= file.reachability;
+        self.roots.extend(parse_patterns(&file.roots)?);
+        self.intra_crate |= file.intra_crate;
+
+        Ok(())
+    }
+
+    pub fn is_ignored(&self, finding: &Finding<'_>) -> bool {
+        let path = finding.path.join("::");
+
+        self.ignore.iter().any(|pattern| pattern.matches(&path))
+            || finding.export.is_some_and(|export| {
+                self.ignore_files
+                    .iter()
+                    .any(|pattern| pattern.matches_path(&export.file))
+            })
+    }
+
+    pub fn is_root(&self, path: &[String]) -> bool {
+        let path = path.join("::");
+
+        self.roots.iter().any(|pattern| pattern.matches(&path))
+    }
+
+    pub fn severity(&self, crate_name: &str) -> Severity {
+        self.severity.get(crate_name).copied().unwrap_or_default()
+    }
+
+    /// Crate name the exports of a package are found under
+    pub fn lib_name(&self, package: &str) -> String {
+        self.lib_names
+            .get(package)
+            .cloned()
+            .unwrap_or_else(|| crate_name(package))
+    }
+
+    /// Package whose library has the given crate name, for messages naming it
+    pub fn package_name<'a>(&'a self, crate_name: &'a str) -> &'a str {
+        self.lib_names
+            .iter()
+            .find(|(_, lib_name)| *lib_name == crate_name)
+            .map_or(crate_name, |(package, _)| package)
+    }
+}
+
+/// Deserializes `[<table>.metadata.purge]` of a manifest, if there is one
+fn read_metadata<T: for<'de> Deserialize<'de>>(
+    manifest_path: &Path,
+    table: &str,
+) -> Result<Option<T>> {
+    let contents = std::fs::read_to_string(manifest_path)
+        .with_context(|| format!("failed to read `{}`", manifest_path.display()))?;
+    let manifest: toml::Table = toml::from_str(&contents)
+        .with_context(|| format!("failed to parse `{}`", manifest_path.display()))?;
+
+    let Some(metadata) = manifest
+        .get(table)
+        .and_then(|table| table.get("metadata"))
+        .and_then(|metadata| metadata.get("purge"))
+    else {
+        return Ok(None);
+    };
+
+    let config = metadata.clone().try_into().with_context(|| {
+        format!(
+            "invalid `[{}.metadata.purge]` in `{}`",
+            table,
+            manifest_path.display()
+        )
+    })?;
+
+    Ok(Some(config))
+}
+
+fn parse_patterns<S: AsRef<str>>(patterns: impl IntoIterator<Item = S>) -> Result<Vec<Pattern>> {
+    patterns
+        .into_iter()
+        .map(|pattern| {
+            Pattern::new(pattern.as_ref())
+                .with_context(|| format!("invalid pattern `{}`", pattern.as_ref()))
+        })
+        .collect()
+}
+
+/// Name code refers to a package by, as crate names can't contain dashes
+pub fn crate_name(package: &str) -> String {
+    package.replace('-', "_")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_merges_configuration_from_every_source() {
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = current_path.join("test_workspaces").join("workspace_6");
+
+        let config = PurgeConfig::load(test_workspace).unwrap();
+
+        let ignore = config
+            .ignore
+            .iter()
+            .map(|pattern| pattern.as_str())
+            .collect::<Vec<_>>();
+        let ignore_files = config
+            .ignore_files
+            .iter()
+            .map(|pattern| pattern.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(ignore, vec!["engine::ignored_*", "engine::legacy"]);
+        assert_eq!(ignore_files, vec!["engine/src/generated.rs"]);
+        assert_eq!(config.filter.exclude, vec!["public_api".to_string()]);
+        assert_eq!(config.severity("engine"), Severity::Warn);
+        assert_eq!(config.severity("app"), Severity::Deny);
+    }
+
+    #[test]
+    fn it_keys_package_settings_by_the_name_of_the_library() {
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = current_path.join("test_workspaces").join("workspace_19");
+
+        let config = PurgeConfig::load(test_workspace).unwrap();
+
+        assert_eq!(config.lib_name("foo-core"), "fcore");
+        assert_eq!(config.package_name("fcore"), "foo-core");
+        assert_eq!(config.severity("fcore"), Severity::Warn);
+    }
+}
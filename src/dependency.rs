@@ -0,0 +1,159 @@
+use crate::config::crate_name;
+use cargo::core::dependency::DepKind;
+use cargo::core::{Dependency, Package};
+use cargo::ops::read_package;
+use cargo::Config;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+/// Manifest table a dependency is declared in
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum DependencyKind {
+    #[serde(rename = "dependencies")]
+    Normal,
+    #[serde(rename = "dev-dependencies")]
+    Development,
+    #[serde(rename = "build-dependencies")]
+    Build,
+}
+
+impl From<DepKind> for DependencyKind {
+    fn from(kind: DepKind) -> Self {
+        match kind {
+            DepKind::Normal => Self::Normal,
+            DepKind::Development => Self::Development,
+            DepKind::Build => Self::Build,
+        }
+    }
+}
+
+impl Display for DependencyKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let table = match self {
+            Self::Normal => "dependencies",
+            Self::Development => "dev-dependencies",
+            Self::Build => "build-dependencies",
+        };
+
+        write!(f, "{}", table)
+    }
+}
+
+/// A dependency that none of the targets able to use it refers to
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct UnusedDependency {
+    pub package: String,
+    pub kind: DependencyKind,
+    /// Name the dependency is declared under, which differs from its package when renamed
+    pub name: String,
+    /// Manifest declaring the dependency, relative to the workspace root
+    pub manifest: PathBuf,
+}
+
+/// Names of the crates the targets of a package refer to
+#[derive(Debug, Default)]
+pub struct CrateReferences {
+    /// From the library, binaries, tests, examples and benches
+    pub targets: BTreeSet<String>,
+    /// From the build script, the only target that can use build dependencies
+    pub build_script: BTreeSet<String>,
+}
+
+/// Lists the dependencies of a package whose name never starts a path in the targets that can
+/// use them
+pub fn unused_dependencies(
+    package: &Package,
+    references: &CrateReferences,
+    root: &Path,
+    config: &Config,
+) -> Vec<UnusedDependency> {
+    let manifest = package
+        .manifest_path()
+        .strip_prefix(root)
+        .unwrap_or(package.manifest_path());
+
+    let mut unused = package
+        .dependencies()
+        .iter()
+        .filter_map(|dependency| {
+            let kind = DependencyKind::from(dependency.kind());
+            let referenced = match kind {
+                DependencyKind::Build => &references.build_script,
+                DependencyKind::Normal | DependencyKind::Development => &references.targets,
+            };
+
+            let name = dependency.name_in_toml();
+
+            if referenced.contains(&referenced_name(dependency, config)) {
+                return None;
+            }
+
+            Some(UnusedDependency {
+                package: package.name().to_string(),
+                kind,
+                name: name.to_string(),
+                manifest: manifest.into(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    // The same dependency may be declared again for specific platforms
+    unused.sort();
+    unused.dedup();
+    unused
+}
+
+/// Name code refers to a dependency by: its rename if it is renamed, and otherwise the name of
+/// its library, which `[lib] name` may set. Only path dependencies can be read without resolving
+/// the dependency graph, so other libraries are assumed to be named after their package.
+fn referenced_name(dependency: &Dependency, config: &Config) -> String {
+    if let Some(rename) = dependency.explicit_name_in_toml() {
+        return crate_name(&rename);
+    }
+
+    let source_id = dependency.source_id();
+    let lib_name = source_id
+        .is_path()
+        .then(|| source_id.url().to_file_path().ok())
+        .flatten()
+        .and_then(|path| read_package(&path.join("Cargo.toml"), source_id, config).ok())
+        .and_then(|(package, _)| {
+            package
+                .targets()
+                .iter()
+                .find(|target| target.is_lib())
+                .map(|lib| lib.crate_name())
+        });
+
+    lib_name.unwrap_or_else(|| crate_name(&dependency.package_name()))
+}
+
+/// A library member of the workspace that nothing in the analyzed workspaces depends on
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct UnusedPackage {
+    pub name: String,
+    /// Relative to the workspace root
+    pub manifest: PathBuf,
+}
+
+/// Whether the package only builds a library for other Rust packages to link against. Binaries,
+/// and libraries built for loading at runtime or from other languages, are used on their own.
+pub fn is_library_only(package: &Package) -> bool {
+    let targets = package.targets();
+
+    targets.iter().any(|target| target.is_lib())
+        && !targets.iter().any(|target| {
+            target.is_bin() || target.is_cdylib() || target.is_dylib() || target.is_staticlib()
+        })
+}
+
+/// Directories of the local packages the package depends on, not counting itself
+pub fn path_dependencies(package: &Package) -> impl Iterator<Item = PathBuf> + '_ {
+    package
+        .dependencies()
+        .iter()
+        .filter_map(|dependency| dependency.source_id().local_path())
+        .filter(|path| path != package.root())
+}
@@ -0,0 +1,176 @@
+use proc_macro2::Span;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::path::PathBuf;
+use syn::{Item, Visibility};
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemKind {
+    Const,
+    Enum,
+    Fn,
+    Macro,
+    Mod,
+    Static,
+    Struct,
+    Trait,
+    TraitAlias,
+    Type,
+    /// A `pub use` re-export
+    Use,
+}
+
+impl ItemKind {
+    pub fn of(item: &Item) -> Option<Self> {
+        match item {
+            Item::Const(_) => Some(Self::Const),
+            Item::Enum(_) => Some(Self::Enum),
+            Item::Fn(_) => Some(Self::Fn),
+            Item::Macro(_) => Some(Self::Macro),
+            Item::Mod(_) => Some(Self::Mod),
+            Item::Static(_) => Some(Self::Static),
+            Item::Struct(_) => Some(Self::Struct),
+            Item::Trait(_) => Some(Self::Trait),
+            Item::TraitAlias(_) => Some(Self::TraitAlias),
+            Item::Type(_) => Some(Self::Type),
+            Item::Use(_) => Some(Self::Use),
+            _ => None,
+        }
+    }
+}
+
+impl Display for ItemKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let keyword = match self {
+            Self::Const => "const",
+            Self::Enum => "enum",
+            Self::Fn => "fn",
+            Self::Macro => "macro",
+            Self::Mod => "mod",
+            Self::Static => "static",
+            Self::Struct => "struct",
+            Self::Trait => "trait",
+            Self::TraitAlias => "trait",
+            Self::Type => "type",
+            Self::Use => "use",
+        };
+
+        write!(f, "{}", keyword)
+    }
+}
+
+/// Where an exported item is defined and what it is
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Export {
+    pub kind: ItemKind,
+    pub visibility: String,
+    pub file: PathBuf,
+    /// 1-based line of the start of the item, after any attributes
+    pub line: usize,
+    /// 1-based column of the start of the item, after any attributes
+    pub column: usize,
+    /// Whether the module defining the item refers to it by name
+    #[serde(skip)]
+    pub used_locally: bool,
+    /// Source the item spans, if it is an item rather than a `pub use` alias
+    #[serde(skip)]
+    pub extent: Option<Extent>,
+    /// File holding the contents of a `mod name;` declaration
+    #[serde(skip)]
+    pub module_file: Option<PathBuf>,
+}
+
+/// Source an item spans, including its attributes and doc comments
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Extent {
+    /// 1-based line
+    pub start_line: usize,
+    /// 0-based column, counted in characters
+    pub start_column: usize,
+    /// 1-based line
+    pub end_line: usize,
+    /// 0-based column just past the end of the item, counted in characters
+    pub end_column: usize,
+}
+
+impl Extent {
+    pub fn new(start: Span, end: Span) -> Self {
+        let (start, end) = (start.start(), end.end());
+
+        Self {
+            start_line: start.line,
+            start_column: start.column,
+            end_line: end.line,
+            end_column: end.column,
+        }
+    }
+}
+
+impl Export {
+    pub fn new(kind: ItemKind, vis: &Visibility, file: PathBuf, span: Span) -> Self {
+        let start = span.start();
+
+        Self {
+            kind,
+            visibility: visibility_to_string(vis),
+            file,
+            line: start.line,
+            column: start.column + 1,
+            used_locally: false,
+            extent: None,
+            module_file: None,
+        }
+    }
+}
+
+/// An item marked with `#[cfg_attr(purge, ...)]` or a `// purge:keep` comment. Marking a module
+/// covers everything beneath it.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Suppression {
+    pub path: Vec<String>,
+    pub file: PathBuf,
+    /// 1-based line of the start of the item, including its attributes
+    pub line: usize,
+}
+
+/// A path written outside the module of the item it refers to
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Reference {
+    /// Absolute path, which may go past the item, e.g. `my_crate::Type::method`
+    pub path: Vec<String>,
+    /// Package the path is written in
+    pub package: String,
+    /// Absolute path of the module the path is written in
+    pub module: Vec<String>,
+    pub file: PathBuf,
+    /// 1-based line
+    pub line: usize,
+    /// 1-based column
+    pub column: usize,
+    /// Whether the path is only compiled for tests, examples or benches
+    pub test: bool,
+    /// Whether the path is a module glob imported with `use module::*`
+    pub glob: bool,
+}
+
+fn visibility_to_string(vis: &Visibility) -> String {
+    match vis {
+        Visibility::Public(_) => "pub".to_string(),
+        Visibility::Restricted(restricted) => {
+            let path = restricted
+                .path
+                .segments
+                .iter()
+                .map(|segment| segment.ident.to_string())
+                .collect::<Vec<_>>()
+                .join("::");
+
+            match restricted.in_token {
+                Some(_) => format!("pub(in {})", path),
+                None => format!("pub({})", path),
+            }
+        }
+        Visibility::Inherited => String::new(),
+    }
+}
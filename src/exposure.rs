@@ -0,0 +1,132 @@
+use crate::export::{ItemKind, Reference};
+use crate::fix::Narrowing;
+use crate::report::Report;
+use crate::tree::Tree;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Finds the `pub` exports that only the library defining them refers to, along with the
+/// narrowest visibility that keeps every one of those references compiling.
+/// `external_exposure` holds the exports nothing outside their library refers to, and
+/// `internal_references` the paths libraries write to refer to themselves.
+pub fn over_exposed(
+    report: &Report,
+    external_exposure: &Tree<String>,
+    internal_references: &[Reference],
+) -> BTreeMap<Vec<String>, Narrowing> {
+    let graph = &report.graph;
+
+    let reported = report
+        .findings()
+        .into_iter()
+        .map(|finding| finding.path)
+        .collect::<BTreeSet<_>>();
+
+    // Narrowing the item a `pub use` re-exports makes the re-export fail to compile
+    let re_exported = graph.re_exports.values().collect::<BTreeSet<_>>();
+
+    // Modules each item is referred to from, and the modules each glob import is written in
+    let mut referring_modules = BTreeMap::<Vec<String>, BTreeSet<&[String]>>::new();
+    let mut glob_imports = BTreeMap::<Vec<String>, BTreeSet<&[String]>>::new();
+
+    for reference in internal_references {
+        for item in graph.resolve(&reference.path, 0) {
+            let modules = match reference.glob {
+                true => glob_imports.entry(item).or_default(),
+                false => referring_modules.entry(item).or_default(),
+            };
+
+            modules.insert(&reference.module);
+        }
+    }
+
+    external_exposure
+        .leaves()
+        .into_iter()
+        .filter(|path| !reported.contains(path) && !re_exported.contains(path))
+        .filter_map(|path| {
+            let export = report.exports.get(&path)?;
+
+            if matches!(export.kind, ItemKind::Mod | ItemKind::Macro) || export.visibility != "pub"
+            {
+                return None;
+            }
+
+            let module = &path[..path.len() - 1];
+            let mut modules = referring_modules.get(&path).cloned().unwrap_or_default();
+
+            if export.used_locally {
+                modules.insert(module);
+            }
+
+            // A glob import may pick the item up from its module, or from a module re-exporting
+            // everything in it
+            for (glob_module, importing_modules) in &glob_imports {
+                let re_exports_module = graph
+                    .glob_re_exports
+                    .contains(&(glob_module.clone(), module.to_vec()));
+
+                if glob_module == module || re_exports_module {
+                    modules.extend(importing_modules);
+                }
+            }
+
+            Some((path.clone(), narrowest(module, &modules)))
+        })
+        .collect()
+}
+
+/// Private items are visible to their module and its descendants, and `pub(super)` ones to the
+/// parent module and its descendants
+fn narrowest(module: &[String], referring_modules: &BTreeSet<&[String]>) -> Narrowing {
+    let all_within = |ancestor: &[String]| {
+        referring_modules
+            .iter()
+            .all(|referring| referring.starts_with(ancestor))
+    };
+
+    if referring_modules.is_empty() {
+        // Something reaches the item in a way the references don't show
+        Narrowing::Crate
+    } else if all_within(module) {
+        Narrowing::Private
+    } else if module.len() > 2 && all_within(&module[..module.len() - 1]) {
+        Narrowing::Super
+    } else {
+        Narrowing::Crate
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn path(path: &str) -> Vec<String> {
+        path.split("::").map(str::to_string).collect()
+    }
+
+    #[test]
+    fn it_picks_the_narrowest_visibility() {
+        let module = path("lib::outer::inner");
+        let inner = path("lib::outer::inner::tests");
+        let outer = path("lib::outer");
+        let other = path("lib::other");
+
+        assert_eq!(
+            narrowest(&module, &[module.as_slice(), inner.as_slice()].into()),
+            Narrowing::Private
+        );
+        assert_eq!(
+            narrowest(&module, &[inner.as_slice(), outer.as_slice()].into()),
+            Narrowing::Super
+        );
+        assert_eq!(
+            narrowest(&module, &[outer.as_slice(), other.as_slice()].into()),
+            Narrowing::Crate
+        );
+        // `pub(super)` in a module at the crate root is the same as `pub(crate)`
+        assert_eq!(
+            narrowest(&outer, &[other.as_slice()].into()),
+            Narrowing::Crate
+        );
+    }
+}
@@ -0,0 +1,290 @@
+use crate::report::{Category, Report};
+use anyhow::{bail, Context, Result};
+use similar::TextDiff;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// How far the visibility of an export is narrowed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Narrowing {
+    /// `pub(crate)`
+    Crate,
+    /// `pub(super)`, for items only the parent of their module and its descendants refer to
+    Super,
+    /// No visibility at all, for items only their own module and its descendants refer to
+    Private,
+}
+
+impl Display for Narrowing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let visibility = match self {
+            Self::Crate => "pub(crate)",
+            Self::Super => "pub(super)",
+            Self::Private => "private",
+        };
+
+        write!(f, "{}", visibility)
+    }
+}
+
+/// Where a `pub` keyword starts and what to replace it with
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VisibilityEdit {
+    /// 1-based line
+    pub line: usize,
+    /// 1-based column, counted in characters
+    pub column: usize,
+    pub narrowing: Narrowing,
+}
+
+/// A file with its contents before and after fixing
+#[derive(Clone, Debug)]
+pub struct FileFix {
+    /// Relative to the workspace root
+    pub path: PathBuf,
+    pub before: String,
+    pub after: String,
+    /// Whether the file is deleted rather than rewritten
+    pub deleted: bool,
+    pub edits: usize,
+}
+
+impl FileFix {
+    pub fn unified_diff(&self) -> String {
+        let path = self.path.display().to_string();
+        let new_path = if self.deleted {
+            "/dev/null".to_string()
+        } else {
+            format!("b/{}", path)
+        };
+
+        TextDiff::from_lines(&self.before, &self.after)
+            .unified_diff()
+            .header(&format!("a/{}", path), &new_path)
+            .to_string()
+    }
+
+    pub fn apply(&self, root: &Path) -> Result<()> {
+        let path = root.join(&self.path);
+
+        if self.deleted {
+            match std::fs::remove_file(&path) {
+                Ok(()) => (),
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => (),
+                Err(error) => {
+                    return Err(error)
+                        .with_context(|| format!("failed to delete `{}`", path.display()))
+                }
+            }
+
+            // Leave no empty module directories behind
+            if let Some(parent) = path.parent() {
+                let _ = std::fs::remove_dir(parent);
+            }
+
+            Ok(())
+        } else {
+            std::fs::write(&path, &self.after)
+                .with_context(|| format!("failed to write `{}`", path.display()))
+        }
+    }
+
+    /// Puts the original contents back
+    pub fn revert(&self, root: &Path) -> Result<()> {
+        let path = root.join(&self.path);
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        std::fs::write(&path, &self.before)
+            .with_context(|| format!("failed to restore `{}`", path.display()))
+    }
+}
+
+/// Works out the new contents of every file defining an unused export. Exports only used in tests
+/// are left alone, as tests in other crates still need them, and so are over-exposed ones, whose
+/// suggested visibility is only a suggestion. So are unused exports named in the signature of an
+/// item that stays public, which other crates could no longer call otherwise.
+pub fn plan_fixes(report: &Report) -> Result<Vec<FileFix>> {
+    let mut edits = BTreeMap::<&Path, Vec<VisibilityEdit>>::new();
+    let named = report.named_in_public_signatures();
+
+    for finding in report.findings() {
+        let Some(export) = finding.export else {
+            continue;
+        };
+
+        if export.visibility != "pub" || named.contains_key(&finding.path) {
+            continue;
+        }
+
+        let narrowing = match finding.category {
+            Category::Unused if export.used_locally => Narrowing::Private,
+            Category::Unused => Narrowing::Crate,
+            Category::TestOnly | Category::OverExposed => continue,
+        };
+
+        edits.entry(&export.file).or_default().push(VisibilityEdit {
+            line: export.line,
+            column: export.column,
+            narrowing,
+        });
+    }
+
+    edits
+        .into_iter()
+        .map(|(path, edits)| {
+            let full_path = report.root.join(path);
+            let before = std::fs::read_to_string(&full_path)
+                .with_context(|| format!("failed to read `{}`", full_path.display()))?;
+            let after = narrow_visibility(&before, &edits)
+                .with_context(|| format!("failed to fix `{}`", path.display()))?;
+
+            Ok(FileFix {
+                path: path.into(),
+                before,
+                after,
+                deleted: false,
+                edits: edits.len(),
+            })
+        })
+        .collect()
+}
+
+/// Rewrites the `pub` keyword at every edit's position
+pub fn narrow_visibility(source: &str, edits: &[VisibilityEdit]) -> Result<String> {
+    let mut offsets = edits
+        .iter()
+        .map(|edit| Ok((byte_offset(source, edit.line, edit.column)?, edit.narrowing)))
+        .collect::<Result<Vec<_>>>()?;
+
+    // Editing from the end keeps the offsets of earlier edits valid
+    offsets.sort_by_key(|(offset, _)| std::cmp::Reverse(*offset));
+    offsets.dedup_by_key(|(offset, _)| *offset);
+
+    let mut result = source.to_string();
+
+    for (offset, narrowing) in offsets {
+        let rest = &result[offset..];
+        let is_pub = rest.starts_with("pub") && rest[3..].starts_with(|c: char| c.is_whitespace());
+
+        if !is_pub {
+            bail!("expected `pub` at byte {}", offset);
+        }
+
+        match narrowing {
+            Narrowing::Crate => result.replace_range(offset..offset + 3, "pub(crate)"),
+            Narrowing::Super => result.replace_range(offset..offset + 3, "pub(super)"),
+            Narrowing::Private => {
+                let whitespace = rest[3..]
+                    .find(|c: char| !c.is_whitespace())
+                    .unwrap_or(rest.len() - 3);
+                result.replace_range(offset..offset + 3 + whitespace, "");
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+fn byte_offset(source: &str, line: usize, column: usize) -> Result<usize> {
+    let line_start = source
+        .split_inclusive('\n')
+        .take(line.saturating_sub(1))
+        .map(str::len)
+        .sum::<usize>();
+
+    let Some(text) = source[line_start..].lines().next() else {
+        bail!("line {} is out of range", line);
+    };
+
+    match text.char_indices().nth(column.saturating_sub(1)) {
+        Some((index, _)) => Ok(line_start + index),
+        None => bail!("column {} is out of range on line {}", column, line),
+    }
+}
+
+/// Refuses to edit files beneath `root` that have uncommitted changes, the same way `cargo fix`
+/// does
+pub fn ensure_clean_tree(root: &Path) -> Result<()> {
+    let output = Command::new("git")
+        .args(["status", "--porcelain", "--", "."])
+        .current_dir(root)
+        .output()
+        .context("failed to run `git status`")?;
+
+    if !output.status.success() {
+        bail!(
+            "no git repository found at `{}` and --fix may make destructive changes; \
+             pass --allow-dirty to fix anyway",
+            root.display()
+        );
+    }
+
+    let changes = String::from_utf8_lossy(&output.stdout);
+
+    if !changes.trim().is_empty() {
+        bail!(
+            "the working tree at `{}` has uncommitted changes and --fix may make destructive \
+             changes; commit or stash them, or pass --allow-dirty to fix anyway\n\n{}",
+            root.display(),
+            changes.trim_end()
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_narrows_pub_to_crate_or_removes_it() {
+        let source = "pub fn a() {}\n\n    pub  struct Ünicode;\npub fn c() {}\n";
+
+        let result = narrow_visibility(
+            source,
+            &[
+                VisibilityEdit {
+                    line: 1,
+                    column: 1,
+                    narrowing: Narrowing::Crate,
+                },
+                VisibilityEdit {
+                    line: 3,
+                    column: 5,
+                    narrowing: Narrowing::Private,
+                },
+                VisibilityEdit {
+                    line: 4,
+                    column: 1,
+                    narrowing: Narrowing::Super,
+                },
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            result,
+            "pub(crate) fn a() {}\n\n    struct Ünicode;\npub(super) fn c() {}\n"
+        );
+    }
+
+    #[test]
+    fn it_refuses_to_edit_anything_but_pub() {
+        let result = narrow_visibility(
+            "pub(crate) fn a() {}\n",
+            &[VisibilityEdit {
+                line: 1,
+                column: 1,
+                narrowing: Narrowing::Crate,
+            }],
+        );
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,157 @@
+use crate::dependency::{UnusedDependency, UnusedPackage};
+use crate::export::ItemKind;
+use crate::fix::Narrowing;
+use crate::report::{Category, Report};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Bumped whenever a field is removed or changes meaning. New fields may be added at any time.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Top level of the `--format json` output
+#[derive(Debug, Serialize)]
+pub struct JsonReport<'a> {
+    pub version: u32,
+    /// Absolute paths of every workspace that was analyzed, the primary workspace first
+    pub workspaces: &'a [PathBuf],
+    pub summary: Summary,
+    /// Every finding, ordered by category and then path
+    pub findings: Vec<JsonFinding<'a>>,
+    /// Suppressions that don't cover any finding
+    pub unneeded_suppressions: Vec<JsonSuppression<'a>>,
+    /// `.rs` files in a package directory that nothing reaches, relative to the primary workspace
+    pub orphan_files: &'a [PathBuf],
+    /// Dependencies no target refers to, with manifests relative to the primary workspace
+    pub unused_dependencies: &'a [UnusedDependency],
+    /// Library members nothing depends on, with manifests relative to the primary workspace
+    pub unused_packages: &'a [UnusedPackage],
+    /// Baseline entries that matched no finding. Only present when a baseline was given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stale_baseline: Option<Vec<JsonBaselineEntry>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Summary {
+    /// Number of exported items, not counting modules
+    pub exports: usize,
+    pub unused: usize,
+    pub test_only: usize,
+    pub over_exposed: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JsonFinding<'a> {
+    pub category: Category,
+    /// Name of the crate the item is exported from
+    #[serde(rename = "crate")]
+    pub crate_name: String,
+    /// Full path of the export, e.g. `package_1::public_module::public_hello`
+    pub path: String,
+    pub kind: Option<ItemKind>,
+    pub visibility: Option<&'a str>,
+    /// File defining the export, relative to the primary workspace
+    pub file: Option<&'a Path>,
+    pub span: Option<Span>,
+    /// Narrowest visibility the export can have: `pub(crate)`, `pub(super)` or `private`. Only
+    /// present for over-exposed exports.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggested_visibility: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JsonSuppression<'a> {
+    pub path: String,
+    pub file: &'a Path,
+    /// 1-based line of the suppressed item, including its attributes
+    pub line: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JsonBaselineEntry {
+    pub category: Category,
+    pub path: String,
+    pub kind: ItemKind,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Span {
+    /// 1-based line
+    pub line: usize,
+    /// 1-based column
+    pub column: usize,
+}
+
+impl<'a> JsonReport<'a> {
+    pub fn new(report: &'a Report) -> Self {
+        let findings = report
+            .findings()
+            .into_iter()
+            .map(|finding| JsonFinding {
+                category: finding.category,
+                crate_name: finding.path[0].clone(),
+                path: finding.path.join("::"),
+                kind: finding.export.map(|export| export.kind),
+                visibility: finding.export.map(|export| export.visibility.as_str()),
+                file: finding.export.map(|export| export.file.as_path()),
+                span: finding.export.map(|export| Span {
+                    line: export.line,
+                    column: export.column,
+                }),
+                suggested_visibility: match finding.category {
+                    Category::OverExposed => report
+                        .suggested_visibility
+                        .get(&finding.path)
+                        .map(Narrowing::to_string),
+                    Category::Unused | Category::TestOnly => None,
+                },
+            })
+            .collect::<Vec<_>>();
+
+        let count = |category| {
+            findings
+                .iter()
+                .filter(|finding| finding.category == category)
+                .count()
+        };
+
+        let summary = Summary {
+            exports: report
+                .exports
+                .values()
+                .filter(|export| export.kind != ItemKind::Mod)
+                .count(),
+            unused: count(Category::Unused),
+            test_only: count(Category::TestOnly),
+            over_exposed: count(Category::OverExposed),
+        };
+
+        Self {
+            version: SCHEMA_VERSION,
+            workspaces: &report.workspaces,
+            summary,
+            findings,
+            unneeded_suppressions: report
+                .unneeded_suppressions
+                .iter()
+                .map(|suppression| JsonSuppression {
+                    path: suppression.path.join("::"),
+                    file: &suppression.file,
+                    line: suppression.line,
+                })
+                .collect(),
+            orphan_files: &report.orphan_files,
+            unused_dependencies: &report.unused_dependencies,
+            unused_packages: &report.unused_packages,
+            stale_baseline: report.stale.as_ref().map(|stale| {
+                stale
+                    .iter()
+                    .map(|entry| JsonBaselineEntry {
+                        category: entry.category,
+                        path: entry.path.join("::"),
+                        kind: entry.kind,
+                    })
+                    .collect()
+            }),
+        }
+    }
+}
@@ -1,73 +1,629 @@
+mod baseline;
+mod cli;
+mod config;
+mod dependency;
+mod export;
+mod exposure;
+mod fix;
+mod json;
+mod reachability;
+mod remove;
+mod report;
+mod sarif;
 mod tree;
+mod usages;
 mod visitor;
+mod why;
 
+use crate::baseline::Baseline;
+use crate::cli::{Args, Cargo, Color, Command, FixMode, Format, PackageFilter};
+use crate::config::{PurgeConfig, Severity};
+use crate::dependency::{CrateReferences, UnusedDependency, UnusedPackage};
+use crate::export::{Export, Reference, Suppression};
+use crate::json::JsonReport;
+use crate::reachability::ReferenceGraph;
+use crate::remove::Sources;
+use crate::report::Report;
+use crate::sarif::to_sarif;
 use crate::tree::Tree;
-use crate::visitor::Visitor;
-use cargo::core::Workspace;
+use crate::visitor::{GlobMode, Usage, Visitor};
+use anyhow::{bail, Context, Result};
+use cargo::core::{Target, Workspace};
 use cargo::Config;
+use clap::Parser;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Display;
+use std::io::{ErrorKind, IsTerminal, Write};
 use std::path::{Path, PathBuf};
 
+/// Exit code when `--deny` is given and there are more findings than allowed
+const EXIT_FINDINGS: i32 = 1;
+/// Exit code when the workspace could not be analyzed
+const EXIT_ERROR: i32 = 2;
+
 pub fn main() {
-    let current_path = std::env::current_dir().unwrap();
-    let additional_workspaces = std::env::args()
-        .skip(2)
-        .map(|arg| current_path.join(arg).canonicalize().unwrap())
-        .collect::<Vec<_>>();
+    let Cargo::Purge(args) = Cargo::parse();
+
+    match run(args) {
+        Ok(true) => {}
+        Ok(false) => std::process::exit(EXIT_FINDINGS),
+        Err(error) => {
+            eprintln!("error: {:#}", error);
+            std::process::exit(EXIT_ERROR);
+        }
+    }
+}
+
+/// Returns whether the report passes the `--deny` gate
+fn run(args: Args) -> Result<bool> {
+    let current_path = std::env::current_dir()?;
+
+    let workspace_path = match &args.manifest_path {
+        Some(manifest_path) => existing_path(&current_path, manifest_path)?,
+        None => current_path.clone(),
+    };
+
+    let additional_workspaces = args
+        .workspaces
+        .iter()
+        .map(|path| existing_path(&current_path, path))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut config = PurgeConfig::load(workspace_path)?;
+    config.workspaces.extend(additional_workspaces);
+    config.filter.packages = args.packages;
+    config.filter.exclude.extend(args.exclude);
+
+    if args.precise_globs {
+        config.glob_mode = GlobMode::Precise;
+    }
+
+    config.reachability |= args.reachability;
+    config.intra_crate |= args.intra_crate;
+
+    let mut report = find_unused_exports(&config)?;
+
+    match &args.command {
+        Some(Command::Usages) => {
+            let usages = usages::export_usages(&report);
+
+            match args.format {
+                Format::Text => {
+                    let mut output = String::new();
+                    usages::write_text(&mut output, &usages, args.quiet)?;
+                    print_text(output)?;
+                }
+                Format::Json => {
+                    let output = usages::JsonUsages::new(&usages);
+                    print_json(&output)?;
+                }
+                Format::Sarif => bail!("usages can't be written as SARIF, as they aren't findings"),
+            }
+
+            return Ok(true);
+        }
+        Some(Command::Why { path }) => {
+            if args.format != Format::Text {
+                bail!("explanations can only be written as text");
+            }
+
+            print_text(why::explain(&report, &config, path)?)?;
+            return Ok(true);
+        }
+        None => {}
+    }
+
+    if let Some(path) = &args.write_baseline {
+        let baseline = Baseline::from_findings(&report.findings());
+        baseline.write(path)?;
+
+        eprintln!(
+            "Wrote {} findings to `{}`",
+            baseline.entries().len(),
+            path.display()
+        );
+        return Ok(true);
+    }
+
+    if let Some(path) = &args.baseline {
+        report.apply_baseline(&Baseline::read(path)?);
+    }
+
+    match args.fix {
+        Some(FixMode::Narrow) => {
+            let fixes = fix::plan_fixes(&report)?;
+
+            if args.dry_run {
+                for file_fix in &fixes {
+                    print_text(file_fix.unified_diff())?;
+                }
+            } else {
+                if !args.allow_dirty {
+                    fix::ensure_clean_tree(&report.root)?;
+                }
 
-    let unused_exports = find_unused_exports(current_path, additional_workspaces);
+                for file_fix in &fixes {
+                    file_fix.apply(&report.root)?;
+                }
 
-    println!("Unused exports:");
-    println!("{}", unused_exports);
+                let edits = fixes.iter().map(|file_fix| file_fix.edits).sum::<usize>();
+                eprintln!("Narrowed {} exports in {} files", edits, fixes.len());
+            }
+
+            return Ok(true);
+        }
+        Some(FixMode::Remove) => {
+            let removals = remove::plan_removals(&report)?;
+
+            if args.dry_run {
+                let all = removals.iter().collect::<Vec<_>>();
+                let fixes = remove::render(&report.root, &all, &mut Sources::default())?;
+
+                for file_fix in &fixes {
+                    print_text(file_fix.unified_diff())?;
+                }
+            } else {
+                if !args.allow_dirty {
+                    fix::ensure_clean_tree(&report.root)?;
+                }
+
+                print_text(remove::remove_unused(&report.root, removals)?)?;
+            }
+
+            return Ok(true);
+        }
+        None => {}
+    }
+
+    match args.format {
+        Format::Text => {
+            let color = match args.color {
+                Color::Auto => std::io::stdout().is_terminal(),
+                Color::Always => true,
+                Color::Never => false,
+            };
+
+            let mut output = String::new();
+            report.write_text(&mut output, color, args.quiet)?;
+            print_text(output)?;
+        }
+        Format::Json => {
+            let output = JsonReport::new(&report);
+            print_json(&output)?;
+        }
+        Format::Sarif => {
+            let output = to_sarif(&report);
+            print_json(&output)?;
+        }
+    }
+
+    if !args.deny {
+        return Ok(true);
+    }
+
+    let max_unused = args.max_unused.unwrap_or(0);
+    let mut passed = true;
+
+    for (crate_name, count) in report.findings_per_package() {
+        if config.severity(&crate_name) == Severity::Deny && count > max_unused {
+            eprintln!(
+                "error: package `{}` has {} findings, at most {} allowed",
+                config.package_name(&crate_name),
+                count,
+                max_unused
+            );
+            passed = false;
+        }
+    }
+
+    Ok(passed)
 }
 
-fn find_unused_exports(
-    workspace_path: PathBuf,
-    additional_workspaces: Vec<PathBuf>,
-) -> Tree<String> {
-    let (exports, mut imports) = visit_workspace(workspace_path);
+/// Writes to stdout. Readers like `head` close the pipe once they have seen enough, which isn't
+/// an error.
+fn print_text(output: impl Display) -> Result<()> {
+    ignore_broken_pipe(write!(std::io::stdout().lock(), "{}", output))
+}
 
-    for workspace_path in additional_workspaces {
-        let (_, workspace_imports) = visit_workspace(workspace_path);
-        imports.extend(workspace_imports);
+fn print_json(output: &impl Serialize) -> Result<()> {
+    let json = serde_json::to_string_pretty(output)?;
+    ignore_broken_pipe(writeln!(std::io::stdout().lock(), "{}", json))
+}
+
+fn ignore_broken_pipe(result: std::io::Result<()>) -> Result<()> {
+    match result {
+        Err(error) if error.kind() == ErrorKind::BrokenPipe => Ok(()),
+        result => Ok(result?),
     }
+}
 
-    exports.filter_by(&imports)
+fn existing_path(current_path: &Path, path: &Path) -> Result<PathBuf> {
+    current_path
+        .join(path)
+        .canonicalize()
+        .with_context(|| format!("`{}` does not exist", path.display()))
 }
 
-fn visit_workspace(workspace_path: PathBuf) -> (Tree<String>, Tree<String>) {
-    let manifest_path = Path::new(&workspace_path).join("Cargo.toml");
+/// Accepts either a workspace directory or the path to its Cargo.toml
+fn manifest_path(path: &Path) -> PathBuf {
+    if path.is_dir() {
+        path.join("Cargo.toml")
+    } else {
+        path.into()
+    }
+}
 
-    let config = Config::default().unwrap();
-    let workspace = Workspace::new(&manifest_path, &config).expect("Failed to load workspace");
+/// Name the paths of a target start with. Only the library is known to other crates by its crate
+/// name, so every other target gets a root of its own, which keeps `crate::` paths in a binary
+/// from resolving to items of the library.
+fn target_root(target: &Target) -> String {
+    match target.is_lib() {
+        true => target.crate_name(),
+        false => format!("{}:{}", target.kind().description(), target.crate_name()),
+    }
+}
+
+/// Everything collected from the members of a single workspace
+struct WorkspaceAnalysis {
+    exports: Tree<String>,
+    export_info: BTreeMap<Vec<String>, Export>,
+    imports: Tree<String>,
+    test_imports: Tree<String>,
+    re_exports: BTreeMap<Vec<String>, Vec<String>>,
+    glob_re_exports: BTreeSet<(Vec<String>, Vec<String>)>,
+    suppressions: Vec<Suppression>,
+    /// `.rs` files in a package directory that no target or module declaration reaches
+    orphan_files: Vec<PathBuf>,
+    unused_dependencies: Vec<UnusedDependency>,
+    /// Members that only build a library, keyed by their directory
+    libraries: BTreeMap<PathBuf, UnusedPackage>,
+    /// Directories of every local package a member depends on
+    path_dependencies: BTreeSet<PathBuf>,
+    graph: ReferenceGraph,
+    /// Files are relative to `root`
+    references: Vec<Reference>,
+    /// Items of the reported libraries with a restricted visibility, keyed by absolute path
+    restricted: BTreeMap<Vec<String>, Export>,
+    /// Imports from everywhere but the library they import from
+    external_imports: Tree<String>,
+    /// Paths libraries write to refer to themselves
+    internal_references: Vec<Reference>,
+    /// Paths the `pub` items of the reported libraries name in their signatures
+    signatures: BTreeMap<Vec<String>, BTreeSet<Vec<String>>>,
+    /// Directory the files of exports and suppressions are relative to
+    root: PathBuf,
+}
+
+fn find_unused_exports(config: &PurgeConfig) -> Result<Report> {
+    let WorkspaceAnalysis {
+        mut exports,
+        mut export_info,
+        mut imports,
+        mut test_imports,
+        re_exports,
+        glob_re_exports,
+        suppressions,
+        orphan_files,
+        unused_dependencies,
+        libraries,
+        mut path_dependencies,
+        mut graph,
+        mut references,
+        restricted,
+        mut external_imports,
+        internal_references,
+        signatures,
+        root,
+    } = visit_workspace(config.workspace.clone(), config.glob_mode, &config.filter)?;
+
+    // Restricted items are exports whose only possible users are in their own crate
+    if config.intra_crate {
+        for (path, export) in restricted {
+            exports.insert_path(&path, None);
+
+            if export.used_locally {
+                imports.insert_path(&path, None);
+            }
+
+            export_info.insert(path, export);
+        }
+    }
+
+    let mut workspace_roots = vec![root.clone()];
+
+    for workspace_path in config.workspaces.clone() {
+        let analysis =
+            visit_workspace(workspace_path, config.glob_mode, &PackageFilter::default())?;
+        workspace_roots.push(analysis.root);
+        // Nothing in another workspace is part of the libraries being reported on
+        external_imports.extend(analysis.imports.clone());
+        external_imports.extend(analysis.test_imports.clone());
+
+        imports.extend(analysis.imports);
+        test_imports.extend(analysis.test_imports);
+        path_dependencies.extend(analysis.path_dependencies);
+        graph.extend(analysis.graph);
+        references.extend(analysis.references);
+    }
+
+    usages::follow_re_exports(&mut references, &re_exports);
+
+    let configured_roots = graph
+        .references
+        .keys()
+        .filter(|item| config.is_root(item))
+        .cloned()
+        .collect::<Vec<_>>();
+    graph.roots.extend(configured_roots);
+
+    external_imports.follow_aliases(&re_exports, &glob_re_exports);
+    let external_exposure = exports.filter_by(&external_imports);
+
+    if config.reachability {
+        (imports, test_imports) = graph.used_exports(&export_info);
+    } else {
+        imports.follow_aliases(&re_exports, &glob_re_exports);
+        test_imports.follow_aliases(&re_exports, &glob_re_exports);
+    }
+
+    let mut report = Report::new(exports, export_info, imports, test_imports);
+    report.root = root;
+    report.orphan_files = orphan_files;
+    report.unused_dependencies = unused_dependencies;
+    report.references = references;
+    report.graph = graph;
+    report.signatures = signatures;
+    report.suggested_visibility =
+        exposure::over_exposed(&report, &external_exposure, &internal_references);
+    report.over_exposed = Tree::new();
+
+    for path in report.suggested_visibility.keys() {
+        report.over_exposed.insert_path(path, None);
+    }
+
+    report.unused_packages = libraries
+        .into_iter()
+        .filter(|(directory, _)| !path_dependencies.contains(directory))
+        .map(|(_, package)| package)
+        .collect();
+    report.apply_suppressions(suppressions);
+    report.retain(|finding| !config.is_ignored(finding));
+    report.workspaces = workspace_roots;
+
+    Ok(report)
+}
+
+fn visit_workspace(
+    workspace_path: PathBuf,
+    glob_mode: GlobMode,
+    filter: &PackageFilter,
+) -> Result<WorkspaceAnalysis> {
+    let manifest_path = manifest_path(&workspace_path);
+
+    let config = Config::default()?;
+    let workspace = Workspace::new(&manifest_path, &config)
+        .with_context(|| format!("failed to load workspace `{}`", manifest_path.display()))?;
+
+    let mut analysis = WorkspaceAnalysis {
+        exports: Tree::new(),
+        export_info: BTreeMap::new(),
+        imports: Tree::new(),
+        test_imports: Tree::new(),
+        re_exports: BTreeMap::new(),
+        glob_re_exports: BTreeSet::new(),
+        suppressions: Vec::new(),
+        orphan_files: Vec::new(),
+        unused_dependencies: Vec::new(),
+        libraries: BTreeMap::new(),
+        path_dependencies: BTreeSet::new(),
+        graph: ReferenceGraph::default(),
+        references: Vec::new(),
+        restricted: BTreeMap::new(),
+        external_imports: Tree::new(),
+        internal_references: Vec::new(),
+        signatures: BTreeMap::new(),
+        root: workspace.root().into(),
+    };
+
+    let mut visited_files = BTreeSet::new();
+
+    for package in workspace.members() {
+        let mut references = CrateReferences::default();
+
+        analysis
+            .path_dependencies
+            .extend(dependency::path_dependencies(package));
+
+        if dependency::is_library_only(package) && filter.includes(&package.name()) {
+            let manifest = package.manifest_path();
 
-    workspace.members().fold(
-        (Tree::new(), Tree::new()),
-        |(mut exports, mut imports), package| {
-            // Todo - can we figure out the entry point from the Package struct?
-            let lib_file_path = Path::new(&package.root()).join("src").join("lib.rs");
-            let main_file_path = Path::new(&package.root()).join("src").join("main.rs");
+            analysis.libraries.insert(
+                package.root().into(),
+                UnusedPackage {
+                    name: package.name().to_string(),
+                    manifest: manifest
+                        .strip_prefix(workspace.root())
+                        .unwrap_or(manifest)
+                        .into(),
+                },
+            );
+        }
 
-            let file_path = if Path::new(&lib_file_path).exists() {
-                lib_file_path
-            } else if Path::new(&main_file_path).exists() {
-                main_file_path
+        for target in package.targets() {
+            let Some(file_path) = target.src_path().path() else {
+                continue;
+            };
+
+            let usage = if target.is_test() || target.is_example() || target.is_bench() {
+                Usage::Test
             } else {
-                panic!("Neither lib.rs nor main.rs found in package");
+                Usage::Production
             };
 
-            let mut visitor = Visitor::new(package.root().into());
-            visitor.visit_file(file_path);
+            let mut visitor = Visitor::new(file_path.parent().unwrap().into(), usage);
+            visitor.glob_mode = glob_mode;
+            visitor.package = package.name().to_string();
+            visitor.visit_file(target_root(target), file_path.into())?;
+
+            if target.is_custom_build() {
+                references.build_script.extend(visitor.crate_references());
+            } else {
+                references.targets.extend(visitor.crate_references());
+            }
+
+            let mut graph = ReferenceGraph {
+                references: std::mem::take(&mut visitor.item_references),
+                modules: std::mem::take(&mut visitor.modules),
+                roots: std::mem::take(&mut visitor.roots),
+                test_roots: std::mem::take(&mut visitor.test_roots),
+                re_exports: visitor.re_exports.clone(),
+                glob_re_exports: visitor.glob_re_exports.clone(),
+            };
+
+            if target.is_bin() || target.is_custom_build() {
+                graph
+                    .roots
+                    .insert(vec![target_root(target), "main".to_string()]);
+            } else if !target.is_lib() {
+                // Test harnesses and bench macros call into tests, examples and benches in ways
+                // that can't be followed, so everything in them counts
+                graph.test_roots.extend(graph.references.keys().cloned());
+            } else if !filter.includes(&package.name()) {
+                // Packages left out of the report may well be used outside the workspace
+                graph.roots.extend(visitor.export_info.keys().cloned());
+            }
+
+            analysis.graph.extend(graph);
+
+            // Only the library target can be depended on, so it is the only source of exports
+            if target.is_lib() && filter.includes(&package.name()) {
+                analysis.exports.extend(visitor.exports_tree);
+
+                for (path, mut export) in visitor.export_info {
+                    // Report files relative to the workspace they belong to
+                    if let Ok(file) = export.file.strip_prefix(workspace.root()) {
+                        export.file = file.into();
+                    }
+
+                    if let Some(module_file) = &export.module_file {
+                        if let Ok(file) = module_file.strip_prefix(workspace.root()) {
+                            export.module_file = Some(file.into());
+                        }
+                    }
+
+                    analysis.export_info.insert(path, export);
+                }
+
+                for (path, mut export) in visitor.restricted {
+                    if let Ok(file) = export.file.strip_prefix(workspace.root()) {
+                        export.file = file.into();
+                    }
+
+                    analysis.restricted.insert(path, export);
+                }
+
+                for mut suppression in visitor.suppressions {
+                    if let Ok(file) = suppression.file.strip_prefix(workspace.root()) {
+                        suppression.file = file.into();
+                    }
+
+                    analysis.suppressions.push(suppression);
+                }
+
+                analysis.re_exports.extend(visitor.re_exports);
+                analysis.glob_re_exports.extend(visitor.glob_re_exports);
+                analysis
+                    .signatures
+                    .extend(std::mem::take(&mut visitor.signatures));
+            }
+
+            visited_files.extend(visitor.visited_files);
 
-            exports.extend(visitor.exports_tree);
-            imports.extend(visitor.imports_tree);
-            (exports, imports)
-        },
-    )
+            for mut reference in visitor.references {
+                if let Ok(file) = reference.file.strip_prefix(workspace.root()) {
+                    reference.file = file.into();
+                }
+
+                if target.is_lib() && reference.path.first() == Some(&target.crate_name()) {
+                    analysis.internal_references.push(reference.clone());
+                }
+
+                analysis.references.push(reference);
+            }
+
+            let mut external_imports = visitor.imports_tree.clone();
+            external_imports.extend(visitor.test_imports_tree.clone());
+
+            // A library doesn't need to export what only it refers to
+            if target.is_lib() {
+                external_imports.0.remove(&target.crate_name());
+            }
+
+            analysis.external_imports.extend(external_imports);
+            analysis.imports.extend(visitor.imports_tree);
+            analysis.test_imports.extend(visitor.test_imports_tree);
+        }
+
+        if filter.includes(&package.name()) {
+            analysis
+                .unused_dependencies
+                .extend(dependency::unused_dependencies(
+                    package,
+                    &references,
+                    workspace.root(),
+                    &config,
+                ));
+        }
+    }
+
+    for package in workspace.members() {
+        if !filter.includes(&package.name()) {
+            continue;
+        }
+
+        let mut files = Vec::new();
+        rust_files(package.root(), &mut files);
+
+        for file in files {
+            if !visited_files.contains(&file) {
+                let file = file.strip_prefix(workspace.root()).unwrap_or(&file);
+                analysis.orphan_files.push(file.into());
+            }
+        }
+    }
+
+    analysis.orphan_files.sort();
+
+    Ok(analysis)
+}
+
+/// Collects the `.rs` files of a package, leaving out build output, hidden directories and
+/// packages nested inside it
+fn rust_files(directory: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(directory) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+
+        if path.is_dir() {
+            if name != "target" && !name.starts_with('.') && !path.join("Cargo.toml").exists() {
+                rust_files(&path, files);
+            }
+        } else if name.ends_with(".rs") {
+            files.push(path);
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use glob::Pattern;
     use serde_json::json;
 
     #[test]
@@ -75,12 +631,17 @@ mod test {
         let current_path = std::env::current_dir().unwrap();
         let test_workspace = current_path.join("test_workspaces").join("workspace_1");
 
-        let unused_exports = find_unused_exports(test_workspace, vec![]);
-        let unused_exports = serde_json::to_value(unused_exports).unwrap();
+        let report = find_unused_exports(&PurgeConfig::new(test_workspace)).unwrap();
+        let unused_exports = serde_json::to_value(report.unused).unwrap();
+        let test_only_exports = serde_json::to_value(report.test_only).unwrap();
 
         assert_eq!(
             unused_exports,
             json!({ "package_1": { "public_hello_unused": null }})
+        );
+        assert_eq!(
+            test_only_exports,
+            json!({ "package_1": { "public_module": { "public_hello": null }}})
         )
     }
 
@@ -95,9 +656,832 @@ mod test {
             .canonicalize()
             .unwrap();
 
-        let unused_exports = find_unused_exports(test_workspace_1, vec![test_workspace_2]);
-        let unused_exports = serde_json::to_value(unused_exports).unwrap();
+        let report = find_unused_exports(&PurgeConfig {
+            workspaces: vec![test_workspace_2],
+            ..PurgeConfig::new(test_workspace_1)
+        })
+        .unwrap();
+        let unused_exports = serde_json::to_value(report.unused).unwrap();
 
         assert_eq!(unused_exports, json!({}))
     }
+
+    #[test]
+    fn it_visits_every_target_of_a_package() {
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = current_path.join("test_workspaces").join("workspace_3");
+
+        let report = find_unused_exports(&PurgeConfig::new(test_workspace)).unwrap();
+        let unused_exports = serde_json::to_value(report.unused).unwrap();
+        let test_only_exports = serde_json::to_value(report.test_only).unwrap();
+
+        assert_eq!(unused_exports, json!({ "multi_target": { "unused": null }}));
+        assert_eq!(
+            test_only_exports,
+            json!({ "multi_target": { "used_by_example": null, "used_by_tests": null }})
+        )
+    }
+
+    #[test]
+    fn it_lists_files_no_module_declaration_reaches() {
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = current_path.join("test_workspaces").join("workspace_3");
+
+        let report = find_unused_exports(&PurgeConfig::new(test_workspace)).unwrap();
+
+        assert_eq!(
+            report.orphan_files,
+            vec![
+                PathBuf::from("multi_target/src/lib.rs"),
+                PathBuf::from("multi_target/src/old/parser.rs"),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_reports_dependencies_no_target_refers_to() {
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = current_path.join("test_workspaces").join("workspace_10");
+
+        let report = find_unused_exports(&PurgeConfig::new(test_workspace)).unwrap();
+        let unused_dependencies = serde_json::to_value(report.unused_dependencies).unwrap();
+
+        assert_eq!(
+            unused_dependencies,
+            json!([
+                {
+                    "package": "app",
+                    "kind": "dependencies",
+                    "name": "spare",
+                    "manifest": "app/Cargo.toml",
+                },
+                {
+                    "package": "app",
+                    "kind": "build-dependencies",
+                    "name": "text-utils",
+                    "manifest": "app/Cargo.toml",
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn it_refers_to_dependencies_by_the_name_of_their_library() {
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = current_path.join("test_workspaces").join("workspace_19");
+
+        let report = find_unused_exports(&PurgeConfig::new(test_workspace)).unwrap();
+
+        assert!(report.unused_dependencies.is_empty());
+        assert_eq!(
+            serde_json::to_value(&report.unused).unwrap(),
+            json!({ "fcore": { "unused": null } })
+        );
+    }
+
+    #[test]
+    fn it_reports_libraries_nothing_depends_on() {
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = current_path.join("test_workspaces").join("workspace_10");
+
+        let report = find_unused_exports(&PurgeConfig::new(test_workspace)).unwrap();
+
+        assert_eq!(
+            report.unused_packages,
+            vec![UnusedPackage {
+                name: "retired".to_string(),
+                manifest: PathBuf::from("libs/retired/Cargo.toml"),
+            }]
+        );
+    }
+
+    #[test]
+    fn it_suggests_narrower_visibility_for_exports_only_their_crate_uses() {
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = current_path.join("test_workspaces").join("workspace_12");
+
+        let report = find_unused_exports(&PurgeConfig::new(test_workspace)).unwrap();
+        let suggestions = report
+            .suggested_visibility
+            .iter()
+            .map(|(path, narrowing)| (path.join("::"), narrowing.to_string()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(serde_json::to_value(&report.unused).unwrap(), json!({}));
+        assert_eq!(
+            suggestions,
+            vec![
+                (
+                    "exposed::outer::inner::crate_wide".to_string(),
+                    "pub(crate)".to_string()
+                ),
+                (
+                    "exposed::outer::inner::for_parent".to_string(),
+                    "pub(super)".to_string()
+                ),
+                (
+                    "exposed::outer::inner::own_module_only".to_string(),
+                    "private".to_string()
+                ),
+                (
+                    "exposed::outer::summarize".to_string(),
+                    "pub(crate)".to_string()
+                ),
+                ("exposed::root_helper".to_string(), "private".to_string()),
+            ]
+        );
+
+        // Suggestions neither fail `--deny` nor get applied by `--fix`
+        assert!(report.findings_per_package().is_empty());
+        assert!(fix::plan_fixes(&report).unwrap().is_empty());
+    }
+
+    #[test]
+    fn it_reports_restricted_items_nothing_in_their_crate_uses() {
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = current_path.join("test_workspaces").join("workspace_12");
+
+        let report = find_unused_exports(&PurgeConfig::new(test_workspace.clone())).unwrap();
+        assert_eq!(serde_json::to_value(&report.unused).unwrap(), json!({}));
+
+        let report = find_unused_exports(&PurgeConfig {
+            intra_crate: true,
+            ..PurgeConfig::new(test_workspace)
+        })
+        .unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&report.unused).unwrap(),
+            json!({
+                "exposed": {
+                    "internal": { "unused_restricted": null },
+                    "outer": { "inner": { "scoped_unused": null } },
+                }
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(&report.test_only).unwrap(),
+            json!({ "exposed": { "internal": { "used_by_tests": null } } })
+        );
+
+        let path = ["exposed", "outer", "inner", "scoped_unused"].map(str::to_string);
+        assert_eq!(report.exports[&path[..]].visibility, "pub(in crate::outer)");
+    }
+
+    #[test]
+    fn it_reports_exports_no_root_reaches() {
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = current_path.join("test_workspaces").join("workspace_11");
+
+        let report = find_unused_exports(&PurgeConfig {
+            reachability: true,
+            ..PurgeConfig::new(test_workspace.clone())
+        })
+        .unwrap();
+        let unused_exports = serde_json::to_value(report.unused).unwrap();
+        let test_only_exports = serde_json::to_value(report.test_only).unwrap();
+
+        assert_eq!(
+            unused_exports,
+            json!({
+                "core_lib": {
+                    "chain_end": null,
+                    "chain_start": null,
+                    "used_by_dead_code": null,
+                }
+            })
+        );
+        assert_eq!(
+            test_only_exports,
+            json!({ "core_lib": { "used_by_tests": null }})
+        );
+
+        let report = find_unused_exports(&PurgeConfig {
+            reachability: true,
+            roots: vec![Pattern::new("core_lib::chain_end").unwrap()],
+            ..PurgeConfig::new(test_workspace)
+        })
+        .unwrap();
+        let unused_exports = serde_json::to_value(report.unused).unwrap();
+
+        assert_eq!(
+            unused_exports,
+            json!({ "core_lib": { "used_by_dead_code": null }})
+        );
+    }
+
+    #[test]
+    fn it_counts_the_references_to_every_export() {
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = current_path.join("test_workspaces").join("workspace_4");
+
+        let report = find_unused_exports(&PurgeConfig::new(test_workspace)).unwrap();
+        let usages = usages::export_usages(&report);
+
+        let mut output = String::new();
+        usages::write_text(&mut output, &usages[4..5], false).unwrap();
+
+        assert_eq!(
+            output,
+            "facade/src/api.rs:3:1 fn facade::api::used_directly: 3 references from consumer, facade\n    \
+             consumer/src/main.rs:1:1 consumer\n    \
+             consumer/src/main.rs:9:5 consumer\n    \
+             facade/src/relative.rs:7:5 facade\n"
+        );
+
+        let counts = usages
+            .iter()
+            .map(|usage| (usage.path.join("::"), usage.references.len()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            counts,
+            vec![
+                ("facade::Thing".to_string(), 1),
+                ("facade::Widget".to_string(), 0),
+                ("facade::api::Widget".to_string(), 2),
+                ("facade::api::unused_function".to_string(), 0),
+                ("facade::api::used_directly".to_string(), 3),
+                ("facade::renamed".to_string(), 1),
+                ("facade::tools::glob_unused".to_string(), 0),
+                ("facade::tools::glob_used".to_string(), 1),
+                ("facade::unused_function".to_string(), 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_follows_re_exports_to_their_definitions() {
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = current_path.join("test_workspaces").join("workspace_4");
+
+        let report = find_unused_exports(&PurgeConfig::new(test_workspace)).unwrap();
+        let unused_exports = serde_json::to_value(report.unused).unwrap();
+
+        assert_eq!(
+            unused_exports,
+            json!({
+                "facade": {
+                    "Widget": null,
+                    "api": { "unused_function": null },
+                    "unused_function": null,
+                }
+            })
+        )
+    }
+
+    #[test]
+    fn it_explains_why_an_export_is_reported() {
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = current_path.join("test_workspaces").join("workspace_4");
+
+        let config = PurgeConfig {
+            glob_mode: GlobMode::Precise,
+            ..PurgeConfig::new(test_workspace)
+        };
+        let report = find_unused_exports(&config).unwrap();
+        let explanation = why::explain(&report, &config, "facade::tools::glob_unused").unwrap();
+
+        assert_eq!(
+            explanation,
+            "fn facade::tools::glob_unused\n\
+             Defined at facade/src/tools.rs:5:1\n\
+             Exported through `pub mod facade::tools`\n\
+             Re-exported by `pub use facade::tools::*` in facade::prelude\n\
+             \n\
+             No references\n\
+             \n\
+             Glob imports:\n    \
+             consumer/src/main.rs:4:1 consumer\n\
+             \n\
+             Reported as unused: its module is only glob imported, and with precise globs an \
+             import only counts the names the importing module refers to\n"
+        );
+
+        let test_workspace = current_path.join("test_workspaces").join("workspace_11");
+        let config = PurgeConfig {
+            reachability: true,
+            ..PurgeConfig::new(test_workspace)
+        };
+        let report = find_unused_exports(&config).unwrap();
+        let explanation = why::explain(&report, &config, "core_lib::used_by_dead_code").unwrap();
+
+        assert!(explanation.contains("    bin:app::never_called (not reachable from any root)\n"));
+        assert!(explanation.ends_with(
+            "Reported as unused: everything referring to it is dead code that no root reaches\n"
+        ));
+
+        assert!(why::explain(&report, &config, "core_lib::missing").is_err());
+    }
+
+    #[test]
+    fn it_keeps_crate_paths_of_binaries_out_of_the_library() {
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = current_path.join("test_workspaces").join("workspace_20");
+
+        let report = find_unused_exports(&PurgeConfig::new(test_workspace)).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&report.unused).unwrap(),
+            json!({ "dual": { "only_bin_crate_path": null } })
+        );
+    }
+
+    #[test]
+    fn it_keeps_glob_imports_when_another_target_names_items() {
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = current_path.join("test_workspaces").join("workspace_13");
+
+        let report = find_unused_exports(&PurgeConfig::new(test_workspace)).unwrap();
+
+        assert_eq!(serde_json::to_value(&report.unused).unwrap(), json!({}));
+        assert_eq!(serde_json::to_value(&report.test_only).unwrap(), json!({}));
+    }
+
+    #[test]
+    fn it_only_counts_what_tests_name_through_a_glob_import() {
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = current_path.join("test_workspaces").join("workspace_14");
+
+        let report = find_unused_exports(&PurgeConfig::new(test_workspace)).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&report.unused).unwrap(),
+            json!({ "checked": { "math": { "never_used": null } } })
+        );
+        assert_eq!(
+            serde_json::to_value(&report.test_only).unwrap(),
+            json!({ "checked": { "math": { "double": null } } })
+        );
+    }
+
+    #[test]
+    fn it_stops_quietly_when_the_output_pipe_closes() {
+        assert!(ignore_broken_pipe(Err(ErrorKind::BrokenPipe.into())).is_ok());
+        assert!(ignore_broken_pipe(Err(ErrorKind::PermissionDenied.into())).is_err());
+    }
+
+    #[test]
+    fn it_fails_on_files_that_dont_parse() {
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = current_path.join("test_workspaces").join("workspace_16");
+
+        let error = find_unused_exports(&PurgeConfig::new(test_workspace)).unwrap_err();
+
+        assert!(error
+            .to_string()
+            .ends_with("broken/src/parsed.rs` at 3:12: expected identifier"));
+    }
+
+    #[test]
+    fn it_reports_items_of_extern_blocks() {
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = current_path.join("test_workspaces").join("workspace_17");
+
+        let report = find_unused_exports(&PurgeConfig::new(test_workspace)).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&report.unused).unwrap(),
+            json!({ "ffi": { "labs": null, "timezone": null } })
+        );
+    }
+
+    #[test]
+    fn it_reports_exported_macros_at_the_crate_root() {
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = current_path.join("test_workspaces").join("workspace_18");
+
+        let report = find_unused_exports(&PurgeConfig::new(test_workspace)).unwrap();
+        let kinds = report
+            .exports
+            .iter()
+            .map(|(path, export)| (path.join("::"), export.kind.to_string()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            serde_json::to_value(&report.unused).unwrap(),
+            json!({ "macros": { "nested_unused": null, "unused_macro": null } })
+        );
+        assert_eq!(
+            kinds,
+            [
+                ("macros::nested_unused", "macro"),
+                ("macros::unused_macro", "macro"),
+                ("macros::used_macro", "macro"),
+            ]
+            .map(|(path, kind)| (path.to_string(), kind.to_string()))
+        );
+    }
+
+    #[test]
+    fn it_follows_cyclic_glob_re_exports_through_a_module_alias() {
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = current_path.join("test_workspaces").join("workspace_15");
+
+        let report = find_unused_exports(&PurgeConfig::new(test_workspace)).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&report.unused).unwrap(),
+            json!({ "cyclic": { "unused": null } })
+        );
+    }
+
+    #[test]
+    fn it_resolves_glob_imports_precisely() {
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = current_path.join("test_workspaces").join("workspace_4");
+
+        let report = find_unused_exports(&PurgeConfig {
+            glob_mode: GlobMode::Precise,
+            ..PurgeConfig::new(test_workspace)
+        })
+        .unwrap();
+        let unused_exports = serde_json::to_value(report.unused).unwrap();
+
+        assert_eq!(
+            unused_exports,
+            json!({
+                "facade": {
+                    "Widget": null,
+                    "api": { "unused_function": null },
+                    "tools": { "glob_unused": null },
+                    "unused_function": null,
+                }
+            })
+        )
+    }
+
+    #[test]
+    fn it_counts_paths_in_type_positions_as_usage() {
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = current_path.join("test_workspaces").join("workspace_5");
+
+        let report = find_unused_exports(&PurgeConfig::new(test_workspace)).unwrap();
+        let unused_exports = serde_json::to_value(report.unused).unwrap();
+
+        assert_eq!(unused_exports, json!({ "shapes": { "Unused": null }}))
+    }
+
+    #[test]
+    fn it_reports_where_each_export_is_defined() {
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = current_path.join("test_workspaces").join("workspace_1");
+
+        let report = find_unused_exports(&PurgeConfig::new(test_workspace)).unwrap();
+
+        assert_eq!(
+            report.to_string(),
+            "Unused exports:\n\
+             lib/package_1/src/lib.rs:4:1 fn package_1::public_hello_unused\n\
+             \n\
+             Exports only used in tests:\n\
+             lib/package_1/src/public_module/mod.rs:4:1 fn package_1::public_module::public_hello\n\
+             \n\
+             Unused packages:\n\
+             lib/package_2/Cargo.toml package_2\n"
+        )
+    }
+
+    #[test]
+    fn it_renders_findings_as_json() {
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = current_path.join("test_workspaces").join("workspace_1");
+
+        // Workspaces are listed by their root, whatever path they were given by
+        let config = PurgeConfig::load(test_workspace.join("Cargo.toml")).unwrap();
+        assert_eq!(config.workspace, test_workspace);
+
+        let report = find_unused_exports(&config).unwrap();
+        let output = serde_json::to_value(JsonReport::new(&report)).unwrap();
+
+        assert_eq!(
+            output,
+            json!({
+                "version": 1,
+                "workspaces": [test_workspace],
+                "summary": { "exports": 6, "unused": 1, "test_only": 1, "over_exposed": 0 },
+                "findings": [
+                    {
+                        "category": "unused",
+                        "crate": "package_1",
+                        "path": "package_1::public_hello_unused",
+                        "kind": "fn",
+                        "visibility": "pub",
+                        "file": "lib/package_1/src/lib.rs",
+                        "span": { "line": 4, "column": 1 },
+                    },
+                    {
+                        "category": "test_only",
+                        "crate": "package_1",
+                        "path": "package_1::public_module::public_hello",
+                        "kind": "fn",
+                        "visibility": "pub",
+                        "file": "lib/package_1/src/public_module/mod.rs",
+                        "span": { "line": 4, "column": 1 },
+                    },
+                ],
+                "unneeded_suppressions": [],
+                "orphan_files": [],
+                "unused_dependencies": [],
+                "unused_packages": [
+                    { "name": "package_2", "manifest": "lib/package_2/Cargo.toml" },
+                ],
+            })
+        )
+    }
+
+    #[test]
+    fn it_renders_findings_as_sarif() {
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = current_path.join("test_workspaces").join("workspace_1");
+
+        // The files of findings are relative to the workspace root, not to the manifest given
+        let report =
+            find_unused_exports(&PurgeConfig::new(test_workspace.join("Cargo.toml"))).unwrap();
+        let output = to_sarif(&report);
+
+        assert_eq!(output["version"], "2.1.0");
+        assert_eq!(
+            output["runs"][0]["originalUriBaseIds"]["%SRCROOT%"]["uri"],
+            format!("file://{}/", test_workspace.display())
+        );
+
+        let rules = &output["runs"][0]["tool"]["driver"]["rules"];
+        assert_eq!(rules[0]["id"], "unused-export");
+        assert_eq!(rules[1]["id"], "test-only-export");
+
+        let results = output["runs"][0]["results"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(
+            results[0],
+            json!({
+                "ruleId": "unused-export",
+                "ruleIndex": 0,
+                "level": "warning",
+                "message": { "text": "`package_1::public_hello_unused` is exported but never used" },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": {
+                            "uri": "lib/package_1/src/lib.rs",
+                            "uriBaseId": "%SRCROOT%",
+                        },
+                        "region": { "startLine": 4, "startColumn": 1 },
+                    },
+                    "logicalLocations": [{ "fullyQualifiedName": "package_1::public_hello_unused" }],
+                }],
+                "partialFingerprints": { "cargoPurgeExport/v1": "b4360caaa8136605" },
+            })
+        );
+        assert_eq!(results[1]["ruleId"], "test-only-export");
+    }
+
+    #[test]
+    fn it_only_reports_exports_of_selected_packages() {
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = current_path.join("test_workspaces").join("workspace_1");
+
+        let mut config = PurgeConfig::new(test_workspace);
+        config.filter.exclude.push("package_1".to_string());
+
+        let report = find_unused_exports(&config).unwrap();
+        let unused_exports = serde_json::to_value(report.unused).unwrap();
+
+        assert_eq!(unused_exports, json!({}))
+    }
+
+    #[test]
+    fn it_counts_findings_per_package() {
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = current_path.join("test_workspaces").join("workspace_1");
+
+        let report = find_unused_exports(&PurgeConfig::new(test_workspace)).unwrap();
+
+        assert_eq!(
+            report.findings_per_package(),
+            BTreeMap::from([("package_1".to_string(), 2)])
+        );
+    }
+
+    #[test]
+    fn it_suppresses_findings_in_the_baseline() {
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = current_path.join("test_workspaces").join("workspace_1");
+
+        let mut report = find_unused_exports(&PurgeConfig::new(test_workspace)).unwrap();
+
+        let baseline: Baseline = serde_json::from_value(json!({
+            "version": 1,
+            "unused": {
+                "fn": { "package_1": { "public_hello_unused": null } },
+                "struct": { "package_1": { "removed": null } },
+            },
+        }))
+        .unwrap();
+
+        report.apply_baseline(&baseline);
+
+        assert_eq!(
+            report.to_string(),
+            "Unused exports:
+
+Exports only used in tests:
+lib/package_1/src/public_module/mod.rs:4:1 fn package_1::public_module::public_hello
+
+Unused packages:
+lib/package_2/Cargo.toml package_2
+
+Stale baseline entries:
+struct package_1::removed
+"
+        );
+    }
+
+    #[test]
+    fn it_applies_workspace_configuration() {
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = current_path.join("test_workspaces").join("workspace_6");
+
+        let config = PurgeConfig::load(test_workspace).unwrap();
+        let report = find_unused_exports(&config).unwrap();
+        let unused_exports = serde_json::to_value(report.unused).unwrap();
+
+        assert_eq!(unused_exports, json!({ "engine": { "unused": null } }));
+    }
+
+    #[test]
+    fn it_skips_suppressed_items_and_reports_unneeded_suppressions() {
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = current_path.join("test_workspaces").join("workspace_7");
+
+        let report = find_unused_exports(&PurgeConfig::new(test_workspace)).unwrap();
+
+        assert_eq!(
+            report.to_string(),
+            "Unused exports:
+ffi/src/lib.rs:21:1 fn ffi::not_kept
+
+Exports only used in tests:
+
+Unneeded suppressions:
+ffi/src/lib.rs:18 ffi::kept_but_used
+"
+        );
+    }
+
+    #[test]
+    fn it_marks_unused_exports_their_own_module_refers_to() {
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = current_path.join("test_workspaces").join("workspace_8");
+
+        let report = find_unused_exports(&PurgeConfig::new(test_workspace)).unwrap();
+        let mut output = String::new();
+        report.write_text(&mut output, false, true).unwrap();
+
+        assert_eq!(
+            output,
+            "widgets/src/lib.rs:7:1 fn widgets::helper (not used outside its crate)
+widgets/src/shapes.rs:3:1 const widgets::shapes::SIDES
+widgets/src/lib.rs:9:1 fn widgets::unused
+"
+        );
+    }
+
+    #[test]
+    fn it_narrows_the_visibility_of_unused_exports() {
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = current_path.join("test_workspaces").join("workspace_8");
+
+        let report = find_unused_exports(&PurgeConfig::new(test_workspace)).unwrap();
+        let diff = fix::plan_fixes(&report)
+            .unwrap()
+            .iter()
+            .map(|file_fix| file_fix.unified_diff())
+            .collect::<String>();
+
+        assert_eq!(
+            diff,
+            "--- a/widgets/src/lib.rs
++++ b/widgets/src/lib.rs
+@@ -4,8 +4,8 @@
+     helper();
+ }
+ 
+-pub fn helper() {}
++fn helper() {}
+ 
+-pub fn unused() {}
++pub(crate) fn unused() {}
+ 
+ pub(crate) fn already_narrow() {}
+--- a/widgets/src/shapes.rs
++++ b/widgets/src/shapes.rs
+@@ -1,3 +1,3 @@
+ pub struct Square;
+ 
+-pub const SIDES: usize = 4;
++pub(crate) const SIDES: usize = 4;
+"
+        );
+    }
+
+    #[test]
+    fn it_keeps_exports_named_in_public_signatures_public() {
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = current_path.join("test_workspaces").join("workspace_21");
+
+        let report = find_unused_exports(&PurgeConfig::new(test_workspace)).unwrap();
+        let mut output = String::new();
+        report.write_text(&mut output, false, true).unwrap();
+
+        assert_eq!(
+            output,
+            "widgets/src/lib.rs:1:1 struct widgets::Gear (named by the signature of `widgets::Widget`)
+widgets/src/lib.rs:3:1 struct widgets::Widget (named by the signature of `widgets::make`)
+widgets/src/lib.rs:11:1 fn widgets::unused
+"
+        );
+
+        let diff = fix::plan_fixes(&report)
+            .unwrap()
+            .iter()
+            .map(|file_fix| file_fix.unified_diff())
+            .collect::<String>();
+
+        assert_eq!(
+            diff,
+            "--- a/widgets/src/lib.rs
++++ b/widgets/src/lib.rs
+@@ -8,4 +8,4 @@
+     Widget { gear: Gear }
+ }
+ 
+-pub fn unused() {}
++pub(crate) fn unused() {}
+"
+        );
+    }
+
+    #[test]
+    fn it_removes_unused_items_and_rolls_back_those_breaking_the_build() {
+        fn copy_dir(from: &Path, to: &Path) {
+            std::fs::create_dir_all(to).unwrap();
+
+            for entry in std::fs::read_dir(from).unwrap().flatten() {
+                let target = to.join(entry.file_name());
+
+                if entry.path().is_dir() {
+                    copy_dir(&entry.path(), &target);
+                } else {
+                    std::fs::copy(entry.path(), target).unwrap();
+                }
+            }
+        }
+
+        let current_path = std::env::current_dir().unwrap();
+        let test_workspace = std::env::temp_dir().join("cargo_purge_workspace_9");
+
+        let _ = std::fs::remove_dir_all(&test_workspace);
+        copy_dir(
+            &current_path.join("test_workspaces").join("workspace_9"),
+            &test_workspace,
+        );
+
+        let report = find_unused_exports(&PurgeConfig::new(test_workspace.clone())).unwrap();
+        let removals = remove::plan_removals(&report).unwrap();
+        let outcome = remove::remove_unused(&report.root, removals).unwrap();
+
+        let removed = outcome
+            .removed
+            .iter()
+            .map(|removal| removal.path.join("::"))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            removed,
+            vec!["widgets::Gadget", "widgets::legacy", "widgets::unused"]
+        );
+
+        // The exact wording of the error depends on the compiler version
+        let [(rolled_back, error)] = &outcome.rolled_back[..] else {
+            panic!("expected a single rollback, got {:?}", outcome.rolled_back);
+        };
+        assert_eq!(rolled_back.path.join("::"), "widgets::LIMIT");
+        assert!(error.starts_with("app/src/main.rs:5:29: error[E0425]"));
+
+        let lib = std::fs::read_to_string(test_workspace.join("widgets/src/lib.rs")).unwrap();
+        assert_eq!(lib, "pub fn used() {}\n\npub const LIMIT: u32 = 10;\n");
+        assert!(!test_workspace.join("widgets/src/legacy.rs").exists());
+        assert!(!test_workspace.join("widgets/src/legacy").exists());
+
+        std::fs::remove_dir_all(&test_workspace).unwrap();
+    }
+
+    #[test]
+    fn it_fails_to_load_a_missing_workspace() {
+        let current_path = std::env::current_dir().unwrap();
+        let missing_workspace = current_path.join("test_workspaces").join("missing");
+
+        let result = find_unused_exports(&PurgeConfig::new(missing_workspace));
+
+        assert!(result.is_err());
+    }
 }
@@ -0,0 +1,179 @@
+use crate::export::{Export, ItemKind};
+use crate::tree::{Tree, MAX_ALIAS_DEPTH};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Which items refer to which, gathered from every target of the analyzed workspaces
+#[derive(Debug, Default)]
+pub struct ReferenceGraph {
+    /// Absolute paths every item refers to, keyed by the absolute path of the item
+    pub references: BTreeMap<Vec<String>, BTreeSet<Vec<String>>>,
+    /// Absolute path of every module
+    pub modules: BTreeSet<Vec<String>>,
+    /// Items that are used without anything referring to them, such as the `main` of a binary
+    pub roots: BTreeSet<Vec<String>>,
+    /// Items only compiled for tests, examples and benches
+    pub test_roots: BTreeSet<Vec<String>>,
+    /// `pub use` aliases mapped to the absolute path of the item they re-export
+    pub re_exports: BTreeMap<Vec<String>, Vec<String>>,
+    /// Modules re-exporting everything from another module with `pub use module::*`
+    pub glob_re_exports: BTreeSet<(Vec<String>, Vec<String>)>,
+}
+
+impl ReferenceGraph {
+    pub fn extend(&mut self, other: ReferenceGraph) {
+        for (item, references) in other.references {
+            self.references.entry(item).or_default().extend(references);
+        }
+
+        self.modules.extend(other.modules);
+        self.roots.extend(other.roots);
+        self.test_roots.extend(other.test_roots);
+        self.re_exports.extend(other.re_exports);
+        self.glob_re_exports.extend(other.glob_re_exports);
+    }
+
+    /// Turns the exports reachable from the roots into trees standing in for the imports: the
+    /// ones reachable from production roots, and the ones reachable from test roots alone
+    pub fn used_exports(
+        &self,
+        exports: &BTreeMap<Vec<String>, Export>,
+    ) -> (Tree<String>, Tree<String>) {
+        let production = self.reachable(&self.roots);
+        let all = self.reachable(&self.roots.union(&self.test_roots).cloned().collect());
+
+        let to_tree = |items: BTreeSet<Vec<String>>| {
+            let mut tree = Tree::new();
+
+            // A module being reachable says nothing about what is inside it
+            for item in items {
+                if exports
+                    .get(&item)
+                    .is_some_and(|export| export.kind != ItemKind::Mod)
+                {
+                    tree.insert_path(&item, None);
+                }
+            }
+
+            tree
+        };
+
+        let test = all.difference(&production).cloned().collect();
+
+        (to_tree(production), to_tree(test))
+    }
+
+    /// Every item a path of references leads to from one of `roots`
+    pub fn reachable(&self, roots: &BTreeSet<Vec<String>>) -> BTreeSet<Vec<String>> {
+        let mut reachable = BTreeSet::new();
+        let mut pending = roots.iter().cloned().collect::<Vec<_>>();
+
+        while let Some(item) = pending.pop() {
+            if !reachable.insert(item.clone()) {
+                continue;
+            }
+
+            let references = self.references.get(&item).into_iter().flatten();
+            let re_export = self.re_exports.get(&item);
+
+            for path in references.chain(re_export) {
+                pending.extend(self.resolve(path, 0));
+            }
+        }
+
+        reachable
+    }
+
+    /// The items a referenced path leads to. Paths into an item, such as `Type::method`,
+    /// lead to the item, and paths through a re-export lead to both the alias and the item
+    /// it re-exports.
+    pub fn resolve(&self, path: &[String], depth: usize) -> Vec<Vec<String>> {
+        if depth > MAX_ALIAS_DEPTH {
+            return Vec::new();
+        }
+
+        for length in (1..=path.len()).rev() {
+            let (prefix, rest) = path.split_at(length);
+
+            if let Some(target) = self.re_exports.get(prefix) {
+                let target = target.iter().chain(rest).cloned().collect::<Vec<_>>();
+                let mut items = vec![prefix.to_vec()];
+                items.extend(self.resolve(&target, depth + 1));
+                return items;
+            }
+
+            if self.modules.contains(prefix) {
+                let mut items = vec![prefix.to_vec()];
+
+                // The rest of the path may have been re-exported from another module
+                for (module, target) in &self.glob_re_exports {
+                    if module == prefix && !rest.is_empty() {
+                        let target = target.iter().chain(rest).cloned().collect::<Vec<_>>();
+                        items.extend(self.resolve(&target, depth + 1));
+                    }
+                }
+
+                return items;
+            }
+
+            if self.references.contains_key(prefix) {
+                return vec![prefix.to_vec()];
+            }
+        }
+
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn path(path: &str) -> Vec<String> {
+        path.split("::").map(str::to_string).collect()
+    }
+
+    #[test]
+    fn it_follows_references_through_re_exports() {
+        let mut graph = ReferenceGraph::default();
+
+        for item in [
+            "app::main",
+            "lib::inner::f",
+            "lib::inner::g",
+            "lib::other::h",
+        ] {
+            graph.references.insert(path(item), BTreeSet::new());
+        }
+
+        for module in ["lib", "lib::inner", "lib::other"] {
+            graph.modules.insert(path(module));
+        }
+
+        graph.references.insert(
+            path("app::main"),
+            [path("lib::api::f"), path("lib::h::method")].into(),
+        );
+        graph
+            .re_exports
+            .insert(path("lib::api"), path("lib::inner"));
+        graph
+            .glob_re_exports
+            .insert((path("lib"), path("lib::other")));
+        graph.roots.insert(path("app::main"));
+
+        let reachable = graph.reachable(&graph.roots);
+
+        assert_eq!(
+            reachable,
+            [
+                path("app::main"),
+                path("lib"),
+                path("lib::api"),
+                path("lib::inner"),
+                path("lib::inner::f"),
+                path("lib::other::h"),
+            ]
+            .into()
+        );
+    }
+}
@@ -0,0 +1,393 @@
+use crate::export::{Export, ItemKind};
+use crate::fix::FileFix;
+use crate::report::{Category, Report};
+use anyhow::{bail, Context, Result};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Display;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Kinds of items that can be deleted without touching anything around them
+const REMOVABLE_KINDS: [ItemKind; 7] = [
+    ItemKind::Const,
+    ItemKind::Enum,
+    ItemKind::Fn,
+    ItemKind::Mod,
+    ItemKind::Static,
+    ItemKind::Struct,
+    ItemKind::Type,
+];
+
+/// An unused item along with the lines it spans
+#[derive(Clone, Debug)]
+pub struct Removal<'a> {
+    pub path: Vec<String>,
+    pub export: &'a Export,
+    /// 1-based lines of `export.file`, including attributes and doc comments
+    pub lines: RangeInclusive<usize>,
+    /// Files holding the contents of a removed `mod name;`, relative to the workspace root
+    pub module_files: Vec<PathBuf>,
+}
+
+/// What `--fix=remove` ended up doing
+#[derive(Debug, Default)]
+pub struct RemovalOutcome<'a> {
+    pub removed: Vec<Removal<'a>>,
+    /// Removals that broke the build, along with the first error `cargo check` reported
+    pub rolled_back: Vec<(Removal<'a>, String)>,
+}
+
+/// Picks the unused items that can be deleted. A module goes as a whole when every export
+/// beneath it is unused, in which case nothing inside it is removed separately.
+pub fn plan_removals(report: &Report) -> Result<Vec<Removal<'_>>> {
+    let unused = report
+        .findings()
+        .into_iter()
+        .filter(|finding| finding.category == Category::Unused)
+        .map(|finding| finding.path)
+        .collect::<BTreeSet<_>>();
+
+    let removable_modules = report
+        .exports
+        .iter()
+        .filter(|(path, export)| {
+            let mut descendants = report
+                .exports
+                .iter()
+                .filter(|(other, _)| other.len() > path.len() && other.starts_with(path))
+                .peekable();
+
+            // Submodules are never findings themselves unless they are empty
+            export.kind == ItemKind::Mod
+                && !export.used_locally
+                && (unused.contains(*path) || descendants.peek().is_some())
+                && descendants.all(|(descendant, export)| {
+                    export.kind == ItemKind::Mod || unused.contains(descendant)
+                })
+        })
+        .map(|(path, _)| path.clone())
+        .collect::<Vec<_>>();
+
+    let mut sources = BTreeMap::<&Path, String>::new();
+    let mut removals = Vec::new();
+
+    for (path, export) in &report.exports {
+        let inside_removed_module = removable_modules
+            .iter()
+            .any(|module| path.len() > module.len() && path.starts_with(module));
+
+        let removable = if export.kind == ItemKind::Mod {
+            removable_modules.contains(path)
+        } else {
+            unused.contains(path) && !export.used_locally
+        };
+
+        let Some(extent) = export.extent else {
+            continue;
+        };
+
+        if !removable
+            || inside_removed_module
+            || export.visibility != "pub"
+            || !REMOVABLE_KINDS.contains(&export.kind)
+        {
+            continue;
+        }
+
+        if !sources.contains_key(export.file.as_path()) {
+            sources.insert(&export.file, read(&report.root, &export.file)?);
+        }
+
+        // Only items that have their lines to themselves can be cut out cleanly
+        let lines = sources[export.file.as_path()].lines().collect::<Vec<_>>();
+        let is_blank = |text: Option<String>| text.is_some_and(|text| text.trim().is_empty());
+
+        let before = lines
+            .get(extent.start_line - 1)
+            .map(|line| line.chars().take(extent.start_column).collect());
+        let after = lines
+            .get(extent.end_line - 1)
+            .map(|line| line.chars().skip(extent.end_column).collect());
+
+        if !is_blank(before) || !is_blank(after) {
+            continue;
+        }
+
+        removals.push(Removal {
+            path: path.clone(),
+            export,
+            lines: extent.start_line..=extent.end_line,
+            module_files: module_files(&report.root, export.module_file.as_deref()),
+        });
+    }
+
+    Ok(removals)
+}
+
+/// The file of a `mod name;` and everything in its directory of submodules
+fn module_files(root: &Path, module_file: Option<&Path>) -> Vec<PathBuf> {
+    let Some(module_file) = module_file else {
+        return Vec::new();
+    };
+
+    let directory = if module_file.ends_with("mod.rs") {
+        module_file.parent().map(Path::to_path_buf)
+    } else {
+        Some(module_file.with_extension(""))
+    };
+
+    let mut files = vec![module_file.to_path_buf()];
+
+    if let Some(directory) = directory {
+        collect_files(root, &directory, &mut files);
+    }
+
+    files.sort();
+    files.dedup();
+    files
+}
+
+fn collect_files(root: &Path, directory: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(root.join(directory)) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = directory.join(entry.file_name());
+
+        if entry.path().is_dir() {
+            collect_files(root, &path, files);
+        } else {
+            files.push(path);
+        }
+    }
+}
+
+/// Original contents of the files removals touch, read before anything is edited
+#[derive(Debug, Default)]
+pub struct Sources(BTreeMap<PathBuf, String>);
+
+impl Sources {
+    fn get(&mut self, root: &Path, file: &Path) -> Result<String> {
+        if !self.0.contains_key(file) {
+            self.0.insert(file.into(), read(root, file)?);
+        }
+
+        Ok(self.0[file].clone())
+    }
+}
+
+/// Works out the files that change when every removal in `removals` is applied to the
+/// original sources
+pub fn render(
+    root: &Path,
+    removals: &[&Removal<'_>],
+    sources: &mut Sources,
+) -> Result<Vec<FileFix>> {
+    let mut lines = BTreeMap::<&Path, Vec<RangeInclusive<usize>>>::new();
+    let mut fixes = Vec::new();
+
+    for removal in removals {
+        lines
+            .entry(&removal.export.file)
+            .or_default()
+            .push(removal.lines.clone());
+
+        for file in &removal.module_files {
+            fixes.push(FileFix {
+                path: file.clone(),
+                before: sources.get(root, file)?,
+                after: String::new(),
+                deleted: true,
+                edits: 0,
+            });
+        }
+    }
+
+    for (file, ranges) in lines {
+        let before = sources.get(root, file)?;
+        let after = remove_lines(&before, &ranges);
+
+        fixes.push(FileFix {
+            path: file.into(),
+            before,
+            after,
+            deleted: false,
+            edits: ranges.len(),
+        });
+    }
+
+    Ok(fixes)
+}
+
+/// Deletes whole lines, along with a blank line left over between two others
+pub fn remove_lines(source: &str, ranges: &[RangeInclusive<usize>]) -> String {
+    let is_removed = |line: usize| ranges.iter().any(|range| range.contains(&line));
+
+    let mut result = Vec::<&str>::new();
+    let mut removed_since_last = false;
+
+    for (index, line) in source.split_inclusive('\n').enumerate() {
+        if is_removed(index + 1) {
+            removed_since_last = true;
+            continue;
+        }
+
+        let is_blank = line.trim().is_empty();
+        let previous_blank = result.last().is_none_or(|line| line.trim().is_empty());
+
+        if removed_since_last && is_blank && previous_blank {
+            continue;
+        }
+
+        removed_since_last = false;
+        result.push(line);
+    }
+
+    if removed_since_last && result.last().is_some_and(|line| line.trim().is_empty()) {
+        result.pop();
+    }
+
+    result.concat()
+}
+
+/// Applies every removal at once, and when that breaks the build, one at a time so that only
+/// the removals breaking it are rolled back
+pub fn remove_unused<'a>(root: &Path, removals: Vec<Removal<'a>>) -> Result<RemovalOutcome<'a>> {
+    if let Some(error) = cargo_check(root)? {
+        bail!(
+            "the workspace doesn't build before removing anything:\n{}",
+            error
+        );
+    }
+
+    let mut sources = Sources::default();
+
+    let all = removals.iter().collect::<Vec<_>>();
+    let fixes = render(root, &all, &mut sources)?;
+    apply(root, &fixes)?;
+
+    if cargo_check(root)?.is_none() {
+        return Ok(RemovalOutcome {
+            removed: removals,
+            rolled_back: Vec::new(),
+        });
+    }
+
+    revert(root, &fixes)?;
+
+    let mut outcome = RemovalOutcome::default();
+
+    for removal in removals {
+        let mut candidate = outcome.removed.iter().collect::<Vec<_>>();
+        candidate.push(&removal);
+
+        let fixes = render(root, &candidate, &mut sources)?;
+        apply(root, &fixes)?;
+
+        match cargo_check(root)? {
+            None => outcome.removed.push(removal),
+            Some(error) => {
+                revert(root, &fixes)?;
+                let accepted = &candidate[..candidate.len() - 1];
+                apply(root, &render(root, accepted, &mut sources)?)?;
+                outcome.rolled_back.push((removal, error));
+            }
+        }
+    }
+
+    Ok(outcome)
+}
+
+fn apply(root: &Path, fixes: &[FileFix]) -> Result<()> {
+    fixes.iter().try_for_each(|fix| fix.apply(root))
+}
+
+fn revert(root: &Path, fixes: &[FileFix]) -> Result<()> {
+    fixes.iter().try_for_each(|fix| fix.revert(root))
+}
+
+fn read(root: &Path, file: &Path) -> Result<String> {
+    let path = root.join(file);
+
+    std::fs::read_to_string(&path).with_context(|| format!("failed to read `{}`", path.display()))
+}
+
+/// Runs `cargo check` on the whole workspace, returning the first error if it fails
+fn cargo_check(root: &Path) -> Result<Option<String>> {
+    let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+
+    let output = Command::new(cargo)
+        .args([
+            "check",
+            "--workspace",
+            "--all-targets",
+            "--quiet",
+            "--message-format",
+            "short",
+        ])
+        .current_dir(root)
+        .output()
+        .context("failed to run `cargo check`")?;
+
+    if output.status.success() {
+        return Ok(None);
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let error = stderr
+        .lines()
+        .find(|line| line.contains("error"))
+        .unwrap_or("`cargo check` failed");
+
+    Ok(Some(error.to_string()))
+}
+
+impl Display for RemovalOutcome<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let location = |removal: &Removal<'_>| {
+            format!(
+                "{}:{} {} {}",
+                removal.export.file.display(),
+                removal.lines.start(),
+                removal.export.kind,
+                removal.path.join("::")
+            )
+        };
+
+        writeln!(f, "Removed:")?;
+        for removal in &self.removed {
+            writeln!(f, "{}", location(removal))?;
+        }
+
+        writeln!(f)?;
+        writeln!(f, "Rolled back:")?;
+        for (removal, error) in &self.rolled_back {
+            writeln!(f, "{}", location(removal))?;
+            writeln!(f, "    {}", error)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_removes_whole_lines_and_the_blank_line_they_leave() {
+        let source = "fn a() {}\n\n/// Docs\nfn b() {\n}\n\nfn c() {}\n";
+
+        assert_eq!(remove_lines(source, &[3..=5]), "fn a() {}\n\nfn c() {}\n");
+        assert_eq!(
+            remove_lines(source, &[1..=1]),
+            "/// Docs\nfn b() {\n}\n\nfn c() {}\n"
+        );
+        assert_eq!(
+            remove_lines(source, &[7..=7]),
+            "fn a() {}\n\n/// Docs\nfn b() {\n}\n"
+        );
+    }
+}
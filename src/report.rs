@@ -0,0 +1,417 @@
+use crate::baseline::{Baseline, BaselineEntry};
+use crate::dependency::{UnusedDependency, UnusedPackage};
+use crate::export::{Export, Reference, Suppression};
+use crate::fix::Narrowing;
+use crate::reachability::ReferenceGraph;
+use crate::tree::Tree;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Write};
+use std::path::PathBuf;
+
+#[derive(Debug, Serialize)]
+pub struct Report {
+    /// Every workspace that was analyzed, the one the exports come from first
+    #[serde(skip)]
+    pub workspaces: Vec<PathBuf>,
+    /// Root directory of the first workspace, which the files of exports are relative to
+    #[serde(skip)]
+    pub root: PathBuf,
+    /// Exports nothing in the analyzed workspaces refers to
+    pub unused: Tree<String>,
+    /// Exports only referred to from tests, examples, benches or `#[cfg(test)]` code
+    pub test_only: Tree<String>,
+    /// Exports only referred to from inside the library defining them
+    pub over_exposed: Tree<String>,
+    /// Narrowest visibility each over-exposed export can have
+    #[serde(skip)]
+    pub suggested_visibility: BTreeMap<Vec<String>, Narrowing>,
+    /// Kind and location of every export, keyed by absolute path
+    #[serde(skip)]
+    pub exports: BTreeMap<Vec<String>, Export>,
+    /// Baseline entries that no longer match a finding, if a baseline was applied
+    #[serde(skip)]
+    pub stale: Option<Vec<BaselineEntry>>,
+    /// Suppressions that don't cover any finding
+    #[serde(skip)]
+    pub unneeded_suppressions: Vec<Suppression>,
+    /// `.rs` files in a package directory that no target or module declaration reaches, relative
+    /// to `root`
+    #[serde(skip)]
+    pub orphan_files: Vec<PathBuf>,
+    /// Dependencies of the reported packages that no target refers to
+    #[serde(skip)]
+    pub unused_dependencies: Vec<UnusedDependency>,
+    /// Library members of the workspace that nothing depends on
+    #[serde(skip)]
+    pub unused_packages: Vec<UnusedPackage>,
+    /// Every path referring to an item outside its module, from every analyzed workspace
+    #[serde(skip)]
+    pub references: Vec<Reference>,
+    /// Which items refer to which, for explaining findings
+    #[serde(skip)]
+    pub graph: ReferenceGraph,
+    /// Absolute paths every `pub` item of the reported libraries names in its signature, keyed
+    /// by the absolute path of the item
+    #[serde(skip)]
+    pub signatures: BTreeMap<Vec<String>, BTreeSet<Vec<String>>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Category {
+    Unused,
+    TestOnly,
+    OverExposed,
+}
+
+/// A single export that was reported, along with where it is defined
+#[derive(Debug)]
+pub struct Finding<'a> {
+    pub category: Category,
+    pub path: Vec<String>,
+    pub export: Option<&'a Export>,
+}
+
+impl Report {
+    pub fn new(
+        exports: Tree<String>,
+        export_info: BTreeMap<Vec<String>, Export>,
+        imports: Tree<String>,
+        test_imports: Tree<String>,
+    ) -> Self {
+        let unused_in_production = exports.filter_by(&imports);
+
+        let mut all_imports = imports;
+        all_imports.extend(test_imports);
+
+        let unused = exports.filter_by(&all_imports);
+        let test_only = unused_in_production.filter_by(&unused);
+
+        Self {
+            workspaces: Vec::new(),
+            root: PathBuf::new(),
+            unused,
+            test_only,
+            over_exposed: Tree::new(),
+            suggested_visibility: BTreeMap::new(),
+            exports: export_info,
+            stale: None,
+            unneeded_suppressions: Vec::new(),
+            orphan_files: Vec::new(),
+            unused_dependencies: Vec::new(),
+            unused_packages: Vec::new(),
+            references: Vec::new(),
+            graph: ReferenceGraph::default(),
+            signatures: BTreeMap::new(),
+        }
+    }
+
+    /// Drops every finding at or beneath a suppressed path and remembers the suppressions that
+    /// didn't cover any
+    pub fn apply_suppressions(&mut self, suppressions: Vec<Suppression>) {
+        let findings = self.findings();
+
+        self.unneeded_suppressions = suppressions
+            .iter()
+            .filter(|suppression| {
+                !findings
+                    .iter()
+                    .any(|finding| finding.path.starts_with(&suppression.path))
+            })
+            .cloned()
+            .collect();
+
+        self.retain(|finding| {
+            !suppressions
+                .iter()
+                .any(|suppression| finding.path.starts_with(&suppression.path))
+        });
+    }
+
+    /// Drops every finding recorded in `baseline` and remembers the entries that matched nothing
+    pub fn apply_baseline(&mut self, baseline: &Baseline) {
+        let current = Baseline::from_findings(&self.findings());
+
+        let stale = baseline
+            .entries()
+            .into_iter()
+            .filter(|entry| !current.contains(entry.category, entry.kind, &entry.path))
+            .collect();
+
+        self.retain(|finding| {
+            !finding.export.is_some_and(|export| {
+                baseline.contains(finding.category, export.kind, &finding.path)
+            })
+        });
+        self.stale = Some(stale);
+    }
+
+    /// Drops every finding `keep` returns false for
+    pub fn retain(&mut self, keep: impl Fn(&Finding<'_>) -> bool) {
+        let mut unused = Tree::new();
+        let mut test_only = Tree::new();
+        let mut over_exposed = Tree::new();
+
+        for finding in self.findings() {
+            if !keep(&finding) {
+                continue;
+            }
+
+            match finding.category {
+                Category::Unused => unused.insert_path(&finding.path, None),
+                Category::TestOnly => test_only.insert_path(&finding.path, None),
+                Category::OverExposed => over_exposed.insert_path(&finding.path, None),
+            }
+        }
+
+        self.unused = unused;
+        self.test_only = test_only;
+        self.over_exposed = over_exposed;
+    }
+
+    /// Number of unused and test-only exports of each package that has any. Over-exposed exports
+    /// are only suggestions, so they don't count towards `--deny`.
+    pub fn findings_per_package(&self) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+
+        for finding in self.findings() {
+            if finding.category == Category::OverExposed {
+                continue;
+            }
+
+            *counts.entry(finding.path[0].clone()).or_default() += 1;
+        }
+
+        counts
+    }
+
+    /// Unused exports that a `pub` item staying public names in its signature, each mapped to
+    /// that item. Narrowing them would break the signature for other crates.
+    pub fn named_in_public_signatures(&self) -> BTreeMap<Vec<String>, Vec<String>> {
+        // `--fix` narrows unused exports, and everything else stays public
+        let mut narrowed = self.unused.leaves().into_iter().collect::<BTreeSet<_>>();
+        let mut named = BTreeMap::new();
+
+        // An export kept public keeps whatever its own signature names public as well
+        loop {
+            let before = named.len();
+
+            for (item, paths) in &self.signatures {
+                if narrowed.contains(item) || !self.exports.contains_key(item) {
+                    continue;
+                }
+
+                for target in paths.iter().flat_map(|path| self.graph.resolve(path, 0)) {
+                    if narrowed.remove(&target) {
+                        named.insert(target, item.clone());
+                    }
+                }
+            }
+
+            if named.len() == before {
+                return named;
+            }
+        }
+    }
+
+    /// Every reported export, ordered by category and then path
+    pub fn findings(&self) -> Vec<Finding<'_>> {
+        [
+            (Category::Unused, &self.unused),
+            (Category::TestOnly, &self.test_only),
+            (Category::OverExposed, &self.over_exposed),
+        ]
+        .into_iter()
+        .flat_map(|(category, tree)| {
+            tree.leaves().into_iter().map(move |path| Finding {
+                category,
+                export: self.exports.get(&path),
+                path,
+            })
+        })
+        .collect()
+    }
+
+    /// Writes the human readable report. `quiet` leaves out everything but the findings.
+    pub fn write_text(&self, f: &mut impl Write, color: bool, quiet: bool) -> std::fmt::Result {
+        let sections = [
+            (Category::Unused, "Unused exports:"),
+            (Category::TestOnly, "Exports only used in tests:"),
+        ];
+
+        let named = self.named_in_public_signatures();
+
+        for (index, (category, heading)) in sections.into_iter().enumerate() {
+            if !quiet {
+                if index > 0 {
+                    writeln!(f)?;
+                }
+
+                writeln!(f, "{}", paint(heading, BOLD, color))?;
+            }
+
+            for finding in self.findings() {
+                if finding.category != category {
+                    continue;
+                }
+
+                match finding.export {
+                    Some(export) => {
+                        write!(
+                            f,
+                            "{}:{}:{} {} {}",
+                            export.file.display(),
+                            export.line,
+                            export.column,
+                            paint(&export.kind.to_string(), YELLOW, color),
+                            finding.path.join("::")
+                        )?;
+
+                        // A public signature naming it needs it public, whoever uses it
+                        if let Some(item) = named.get(&finding.path) {
+                            write!(f, " (named by the signature of `{}`)", item.join("::"))?;
+                        } else if category == Category::Unused && export.used_locally {
+                            // Its own module refers to it by name, so only the export is unused
+                            write!(f, " (not used outside its crate)")?;
+                        }
+
+                        writeln!(f)?;
+                    }
+                    None => writeln!(f, "{}", finding.path.join("::"))?,
+                }
+            }
+        }
+
+        let over_exposed = self
+            .findings()
+            .into_iter()
+            .filter(|finding| finding.category == Category::OverExposed)
+            .collect::<Vec<_>>();
+
+        if !over_exposed.is_empty() {
+            if !quiet {
+                writeln!(f)?;
+                writeln!(
+                    f,
+                    "{}",
+                    paint("Exports only used inside their crate:", BOLD, color)
+                )?;
+            }
+
+            for finding in over_exposed {
+                let Some(export) = finding.export else {
+                    continue;
+                };
+
+                let narrowing = self
+                    .suggested_visibility
+                    .get(&finding.path)
+                    .copied()
+                    .unwrap_or(Narrowing::Crate);
+
+                writeln!(
+                    f,
+                    "{}:{}:{} {} {} can be {}",
+                    export.file.display(),
+                    export.line,
+                    export.column,
+                    paint(&export.kind.to_string(), YELLOW, color),
+                    finding.path.join("::"),
+                    narrowing
+                )?;
+            }
+        }
+
+        if !self.unused_packages.is_empty() {
+            if !quiet {
+                writeln!(f)?;
+                writeln!(f, "{}", paint("Unused packages:", BOLD, color))?;
+            }
+
+            for package in &self.unused_packages {
+                writeln!(f, "{} {}", package.manifest.display(), package.name)?;
+            }
+        }
+
+        if !self.orphan_files.is_empty() {
+            if !quiet {
+                writeln!(f)?;
+                writeln!(f, "{}", paint("Orphan files:", BOLD, color))?;
+            }
+
+            for file in &self.orphan_files {
+                writeln!(f, "{}", file.display())?;
+            }
+        }
+
+        if !self.unused_dependencies.is_empty() {
+            if !quiet {
+                writeln!(f)?;
+                writeln!(f, "{}", paint("Unused dependencies:", BOLD, color))?;
+            }
+
+            for dependency in &self.unused_dependencies {
+                writeln!(
+                    f,
+                    "{} {} {}",
+                    dependency.manifest.display(),
+                    paint(&format!("[{}]", dependency.kind), YELLOW, color),
+                    dependency.name
+                )?;
+            }
+        }
+
+        if !self.unneeded_suppressions.is_empty() {
+            if !quiet {
+                writeln!(f)?;
+                writeln!(f, "{}", paint("Unneeded suppressions:", BOLD, color))?;
+            }
+
+            for suppression in &self.unneeded_suppressions {
+                writeln!(
+                    f,
+                    "{}:{} {}",
+                    suppression.file.display(),
+                    suppression.line,
+                    suppression.path.join("::")
+                )?;
+            }
+        }
+
+        if let Some(stale) = self.stale.as_ref().filter(|stale| !stale.is_empty()) {
+            if !quiet {
+                writeln!(f)?;
+                writeln!(f, "{}", paint("Stale baseline entries:", BOLD, color))?;
+            }
+
+            for entry in stale {
+                writeln!(
+                    f,
+                    "{} {}",
+                    paint(&entry.kind.to_string(), YELLOW, color),
+                    entry.path.join("::")
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+const BOLD: &str = "\x1b[1m";
+const YELLOW: &str = "\x1b[33m";
+
+fn paint(text: &str, style: &str, color: bool) -> String {
+    if color {
+        format!("{}{}\x1b[0m", style, text)
+    } else {
+        text.to_string()
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_text(f, false, false)
+    }
+}
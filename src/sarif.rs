@@ -0,0 +1,145 @@
+use crate::report::{Category, Finding, Report};
+use serde_json::{json, Value};
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Version of the fingerprint below, bumped whenever its inputs change
+const FINGERPRINT_KEY: &str = "cargoPurgeExport/v1";
+
+fn rule_id(category: Category) -> &'static str {
+    match category {
+        Category::Unused => "unused-export",
+        Category::TestOnly => "test-only-export",
+        Category::OverExposed => "over-exposed-export",
+    }
+}
+
+fn rule(category: Category) -> Value {
+    let (name, description) = match category {
+        Category::Unused => (
+            "UnusedExport",
+            "Exported item that nothing in the analyzed workspaces uses",
+        ),
+        Category::TestOnly => (
+            "TestOnlyExport",
+            "Exported item that is only used by tests, examples or benches",
+        ),
+        Category::OverExposed => (
+            "OverExposedExport",
+            "Exported item that is only used inside the crate defining it",
+        ),
+    };
+
+    json!({
+        "id": rule_id(category),
+        "name": name,
+        "shortDescription": { "text": description },
+        "defaultConfiguration": { "level": "warning" },
+    })
+}
+
+/// FNV-1a, which unlike the standard library's hasher is guaranteed to be stable between builds
+fn fingerprint(input: &str) -> String {
+    let hash = input.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+
+    format!("{:016x}", hash)
+}
+
+fn result(finding: &Finding, rule_index: usize, named_by: Option<&Vec<String>>) -> Value {
+    let path = finding.path.join("::");
+    let kind = finding
+        .export
+        .map(|export| export.kind.to_string())
+        .unwrap_or_default();
+
+    let used_locally = finding.export.is_some_and(|export| export.used_locally);
+
+    let message = match (finding.category, named_by) {
+        (Category::Unused, Some(item)) => format!(
+            "`{}` is exported but never used, although `{}` names it in its signature",
+            path,
+            item.join("::")
+        ),
+        (Category::Unused, None) if used_locally => {
+            format!("`{}` is exported but not used outside its crate", path)
+        }
+        (Category::Unused, None) => format!("`{}` is exported but never used", path),
+        (Category::TestOnly, _) => format!("`{}` is exported but only used by tests", path),
+        (Category::OverExposed, _) => {
+            format!("`{}` is exported but only used inside its crate", path)
+        }
+    };
+
+    // Line numbers are left out so findings keep their identity when unrelated code moves
+    let fingerprint_input = format!("{}:{}:{}", rule_id(finding.category), kind, path);
+
+    let mut location = json!({
+        "logicalLocations": [{ "fullyQualifiedName": path }],
+    });
+
+    if let Some(export) = finding.export {
+        location["physicalLocation"] = json!({
+            "artifactLocation": {
+                "uri": export.file.to_string_lossy().replace('\\', "/"),
+                "uriBaseId": "%SRCROOT%",
+            },
+            "region": {
+                "startLine": export.line,
+                "startColumn": export.column,
+            },
+        });
+    }
+
+    json!({
+        "ruleId": rule_id(finding.category),
+        "ruleIndex": rule_index,
+        "level": "warning",
+        "message": { "text": message },
+        "locations": [location],
+        "partialFingerprints": { FINGERPRINT_KEY: fingerprint(&fingerprint_input) },
+    })
+}
+
+/// Renders a report as a SARIF 2.1.0 log with a single run
+pub fn to_sarif(report: &Report) -> Value {
+    let categories = [Category::Unused, Category::TestOnly, Category::OverExposed];
+    let named = report.named_in_public_signatures();
+
+    let results = report
+        .findings()
+        .iter()
+        .map(|finding| {
+            let rule_index = categories
+                .iter()
+                .position(|category| *category == finding.category)
+                .unwrap();
+
+            result(finding, rule_index, named.get(&finding.path))
+        })
+        .collect::<Vec<_>>();
+
+    let mut run = json!({
+        "tool": {
+            "driver": {
+                "name": env!("CARGO_PKG_NAME"),
+                "version": env!("CARGO_PKG_VERSION"),
+                "rules": categories.into_iter().map(rule).collect::<Vec<_>>(),
+            },
+        },
+        "results": results,
+    });
+
+    if !report.root.as_os_str().is_empty() {
+        run["originalUriBaseIds"] = json!({
+            "%SRCROOT%": { "uri": format!("file://{}/", report.root.display()) },
+        });
+    }
+
+    json!({
+        "$schema": SCHEMA,
+        "version": "2.1.0",
+        "runs": [run],
+    })
+}
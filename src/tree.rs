@@ -1,9 +1,13 @@
 use serde::{Deserialize, Serialize};
 use std::collections::btree_map::Entry;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::Display;
 use std::hash::Hash;
 
+/// Re-exports can point at each other, but never this deep in working code. Bounds how far
+/// `Tree::follow_aliases` and everything else following alias chains goes.
+pub const MAX_ALIAS_DEPTH: usize = 16;
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Tree<T: Clone + Eq + Hash + PartialEq + Ord>(pub BTreeMap<T, Option<Tree<T>>>);
 
@@ -27,20 +31,23 @@ impl<T: Clone + Eq + Hash + PartialEq + Ord> Tree<T> {
         Self(BTreeMap::new())
     }
 
+    /// Merges `other` into the tree. A leaf covers everything beneath it, so it wins over a
+    /// subtree at the same key from either side.
     pub fn extend(&mut self, other: Tree<T>) {
         for (key, value) in other.0 {
-            self.0
-                .entry(key)
-                .and_modify(|e| {
-                    if let Some(e) = e {
-                        if let Some(value) = &value {
-                            e.extend(value.clone());
-                        }
-                    } else {
-                        *e = value.clone();
+            match (self.0.entry(key), value) {
+                (Entry::Vacant(entry), value) => {
+                    entry.insert(value);
+                }
+                (Entry::Occupied(mut entry), Some(value)) => {
+                    if let Some(existing) = entry.get_mut() {
+                        existing.extend(value);
                     }
-                })
-                .or_insert(value);
+                }
+                (Entry::Occupied(mut entry), None) => {
+                    entry.insert(None);
+                }
+            }
         }
     }
 
@@ -52,6 +59,106 @@ impl<T: Clone + Eq + Hash + PartialEq + Ord> Tree<T> {
         self.0.entry(key)
     }
 
+    /// Full paths of every leaf in the tree, in order
+    pub fn leaves(&self) -> Vec<Vec<T>> {
+        let mut leaves = Vec::new();
+
+        for (key, sub_tree) in &self.0 {
+            match sub_tree {
+                Some(sub_tree) => {
+                    for mut path in sub_tree.leaves() {
+                        path.insert(0, key.clone());
+                        leaves.push(path);
+                    }
+                }
+                None => leaves.push(vec![key.clone()]),
+            }
+        }
+
+        leaves
+    }
+
+    /// Looks up the node at `path`. A leaf part way along the path covers everything beneath it.
+    pub fn get(&self, path: &[T]) -> Option<Option<&Tree<T>>> {
+        let (first, rest) = path.split_first()?;
+
+        match self.0.get(first)? {
+            None => Some(None),
+            Some(sub_tree) if rest.is_empty() => Some(Some(sub_tree)),
+            Some(sub_tree) => sub_tree.get(rest),
+        }
+    }
+
+    /// Merges `node` into the tree at `path`, creating any missing parents along the way
+    pub fn insert_path(&mut self, path: &[T], node: Option<Tree<T>>) {
+        let Some((last, parents)) = path.split_last() else {
+            return;
+        };
+
+        let mut current = self;
+
+        for key in parents {
+            match current
+                .0
+                .entry(key.clone())
+                .or_insert_with(|| Some(Tree::new()))
+            {
+                Some(sub_tree) => current = sub_tree,
+                // A leaf already covers everything beneath it
+                None => return,
+            }
+        }
+
+        match (current.0.get_mut(last), node) {
+            (Some(Some(existing)), Some(node)) => existing.extend(node),
+            (Some(None), _) => (),
+            (_, node) => current.insert(last.clone(), node),
+        }
+    }
+
+    /// Copies whatever is found at an alias to its target, until every alias chain has been
+    /// followed. Glob aliases copy the children of a module into their target module, as the
+    /// names they re-export aren't known up front. Using the target doesn't use the alias, so
+    /// nothing is copied the other way. Modules re-exporting each other can form cycles that
+    /// never settle, so chains are only followed `MAX_ALIAS_DEPTH` aliases deep.
+    pub fn follow_aliases(
+        &mut self,
+        aliases: &BTreeMap<Vec<T>, Vec<T>>,
+        glob_aliases: &BTreeSet<(Vec<T>, Vec<T>)>,
+    ) {
+        for _ in 0..MAX_ALIAS_DEPTH {
+            let before = self.clone();
+
+            for (alias, target) in aliases {
+                if let Some(node) = before.get(alias) {
+                    self.insert_path(target, node.cloned());
+                }
+            }
+
+            for (module, target) in glob_aliases {
+                match before.get(module) {
+                    Some(None) => self.insert_path(target, None),
+                    Some(Some(sub_tree)) => {
+                        for (key, node) in &sub_tree.0 {
+                            // Re-exporting a submodule's glob mustn't copy that submodule into itself
+                            if target.starts_with(module) && target.get(module.len()) == Some(key) {
+                                continue;
+                            }
+
+                            let path = target.iter().chain([key]).cloned().collect::<Vec<_>>();
+                            self.insert_path(&path, node.clone());
+                        }
+                    }
+                    None => (),
+                }
+            }
+
+            if *self == before {
+                break;
+            }
+        }
+    }
+
     pub fn filter_by(&self, other: &Tree<T>) -> Tree<T> {
         let mut filtered_nodes = BTreeMap::new();
 
@@ -65,6 +172,12 @@ impl<T: Clone + Eq + Hash + PartialEq + Ord> Tree<T> {
                         filtered_nodes.insert(key.clone(), Some(filtered_tree));
                     }
                 }
+                (Some(sub_tree), None) => {
+                    let filtered_tree = sub_tree.filter_by(&Tree::new());
+                    if !filtered_tree.0.is_empty() {
+                        filtered_nodes.insert(key.clone(), Some(filtered_tree));
+                    }
+                }
                 (None, None) => {
                     filtered_nodes.insert(key.clone(), None);
                 }
@@ -91,7 +204,7 @@ impl<T: Clone + Display + Eq + Hash + PartialEq + Ord> Display for Tree<T> {
                 };
 
                 if let Some(subtree) = maybe_subtree {
-                    print_tree(&subtree, new_prefix, f);
+                    print_tree(subtree, new_prefix, f);
                 } else {
                     writeln!(f, "{}", new_prefix).unwrap();
                 }
@@ -180,6 +293,25 @@ mod tests {
         assert!(merged_sub_tree.0.contains_key("sub_key2"));
     }
 
+    #[test]
+    fn it_keeps_leaves_over_subtrees_when_merging() {
+        let mut glob = Tree::new();
+        glob.0.insert("module".to_string(), None);
+
+        let mut sub_tree = Tree::new();
+        sub_tree.0.insert("item".to_string(), None);
+        let mut items = Tree::new();
+        items.0.insert("module".to_string(), Some(sub_tree));
+
+        let mut tree1 = glob.clone();
+        tree1.extend(items.clone());
+        assert_eq!(tree1, glob);
+
+        let mut tree2 = items;
+        tree2.extend(glob.clone());
+        assert_eq!(tree2, glob);
+    }
+
     #[test]
     fn it_serializes_as_expected() {
         let mut tree = Tree::new();
@@ -228,4 +360,179 @@ mod tests {
         // Assert the result matches the expected value
         assert_eq!(filtered_tree, expected_result);
     }
+
+    #[test]
+    fn it_lists_the_path_of_every_leaf() {
+        let tree: Tree<String> = serde_json::from_value(json!({
+            "A": {
+                "B": null,
+                "C": {
+                    "D": null
+                }
+            },
+            "E": null
+        }))
+        .unwrap();
+
+        let leaves = tree
+            .leaves()
+            .into_iter()
+            .map(|path| path.join("::"))
+            .collect::<Vec<_>>();
+
+        assert_eq!(leaves, vec!["A::B", "A::C::D", "E"]);
+    }
+
+    #[test]
+    fn it_follows_aliases_towards_their_target() {
+        let mut tree: Tree<String> = serde_json::from_value(json!({
+            "crate_a": {
+                "Thing": null,
+                "api": {
+                    "Widget": null
+                }
+            },
+        }))
+        .unwrap();
+
+        let aliases = BTreeMap::from([
+            (
+                vec!["crate_a".to_string(), "Thing".to_string()],
+                vec![
+                    "crate_a".to_string(),
+                    "inner".to_string(),
+                    "Thing".to_string(),
+                ],
+            ),
+            (
+                vec!["crate_a".to_string(), "Widget".to_string()],
+                vec![
+                    "crate_a".to_string(),
+                    "api".to_string(),
+                    "Widget".to_string(),
+                ],
+            ),
+        ]);
+
+        tree.follow_aliases(&aliases, &BTreeSet::new());
+
+        let expected_result: Tree<String> = serde_json::from_value(json!({
+            "crate_a": {
+                "Thing": null,
+                "api": {
+                    "Widget": null
+                },
+                "inner": {
+                    "Thing": null
+                }
+            },
+        }))
+        .unwrap();
+
+        assert_eq!(tree, expected_result);
+    }
+
+    #[test]
+    fn it_follows_chains_of_aliases() {
+        let mut tree: Tree<String> = serde_json::from_value(json!({
+            "crate_a": {
+                "prelude": {
+                    "Thing": null
+                }
+            },
+        }))
+        .unwrap();
+
+        let aliases = BTreeMap::from([
+            (
+                vec![
+                    "crate_a".to_string(),
+                    "prelude".to_string(),
+                    "Thing".to_string(),
+                ],
+                vec!["crate_a".to_string(), "Thing".to_string()],
+            ),
+            (
+                vec!["crate_a".to_string(), "Thing".to_string()],
+                vec![
+                    "crate_a".to_string(),
+                    "inner".to_string(),
+                    "Thing".to_string(),
+                ],
+            ),
+        ]);
+
+        tree.follow_aliases(&aliases, &BTreeSet::new());
+
+        assert_eq!(
+            tree.get(&[
+                "crate_a".to_string(),
+                "inner".to_string(),
+                "Thing".to_string()
+            ]),
+            Some(None)
+        );
+    }
+
+    #[test]
+    fn it_follows_glob_aliases_into_their_target_module() {
+        let mut tree: Tree<String> = serde_json::from_value(json!({
+            "crate_a": {
+                "Thing": null,
+                "prelude": null
+            },
+        }))
+        .unwrap();
+
+        let glob_aliases = BTreeSet::from([
+            (
+                vec!["crate_a".to_string()],
+                vec!["crate_a".to_string(), "inner".to_string()],
+            ),
+            (
+                vec!["crate_a".to_string(), "prelude".to_string()],
+                vec!["crate_a".to_string(), "tools".to_string()],
+            ),
+        ]);
+
+        tree.follow_aliases(&BTreeMap::new(), &glob_aliases);
+
+        let expected_result: Tree<String> = serde_json::from_value(json!({
+            "crate_a": {
+                "Thing": null,
+                "inner": {
+                    "Thing": null,
+                    "prelude": null,
+                    "tools": null
+                },
+                "prelude": null,
+                "tools": null
+            },
+        }))
+        .unwrap();
+
+        assert_eq!(tree, expected_result);
+    }
+
+    #[test]
+    fn it_keeps_subtrees_missing_from_the_filter() {
+        let tree: Tree<String> = serde_json::from_value(json!({
+            "A": {
+                "B": null,
+                "C": {}
+            },
+        }))
+        .unwrap();
+
+        let filtered_tree = tree.filter_by(&Tree::new());
+
+        let expected_result: Tree<String> = serde_json::from_value(json!({
+            "A": {
+                "B": null
+            },
+        }))
+        .unwrap();
+
+        assert_eq!(filtered_tree, expected_result);
+    }
 }
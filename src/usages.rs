@@ -0,0 +1,229 @@
+use crate::export::{Export, ItemKind, Reference};
+use crate::report::Report;
+use crate::tree::MAX_ALIAS_DEPTH;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::path::Path;
+
+/// Bumped whenever a field of the `usages --format json` output is removed or changes meaning,
+/// independently of the report's `SCHEMA_VERSION`. New fields may be added at any time.
+pub const USAGES_SCHEMA_VERSION: u32 = 1;
+
+/// Every reference to an export, for `cargo purge usages`
+#[derive(Debug)]
+pub struct ExportUsages<'a> {
+    pub path: &'a [String],
+    pub export: &'a Export,
+    /// Ordered by package, then by location
+    pub references: Vec<&'a Reference>,
+}
+
+impl<'a> ExportUsages<'a> {
+    /// Packages referring to the export
+    pub fn packages(&self) -> BTreeSet<&'a str> {
+        self.references
+            .iter()
+            .map(|reference| reference.package.as_str())
+            .collect()
+    }
+}
+
+/// Lists the references to every export other than modules, ordered by path. References
+/// through a `pub use` count for both the alias and the item it re-exports.
+pub fn export_usages(report: &Report) -> Vec<ExportUsages<'_>> {
+    let mut references = BTreeMap::<&[String], Vec<&Reference>>::new();
+
+    for reference in &report.references {
+        // `Type::method` refers to `Type`
+        for length in 1..=reference.path.len() {
+            let path = &reference.path[..length];
+
+            if let Some((path, _)) = report.exports.get_key_value(path) {
+                references.entry(path).or_default().push(reference);
+            }
+        }
+    }
+
+    report
+        .exports
+        .iter()
+        .filter(|(_, export)| export.kind != ItemKind::Mod)
+        .map(|(path, export)| {
+            let mut references = references.remove(path.as_slice()).unwrap_or_default();
+            sort_references(&mut references);
+
+            ExportUsages {
+                path,
+                export,
+                references,
+            }
+        })
+        .collect()
+}
+
+/// Every reference to the item at `path` or to something inside it
+pub fn references_to<'a>(references: &'a [Reference], path: &[String]) -> Vec<&'a Reference> {
+    let mut references = references
+        .iter()
+        .filter(|reference| reference.path.starts_with(path))
+        .collect();
+
+    sort_references(&mut references);
+    references
+}
+
+/// Orders references by package and location, keeping one per location
+fn sort_references(references: &mut Vec<&Reference>) {
+    references.sort_by(|a, b| {
+        (&a.package, &a.file, a.line, a.column).cmp(&(&b.package, &b.file, b.line, b.column))
+    });
+    references.dedup_by(|a, b| (&a.file, a.line, a.column) == (&b.file, b.line, b.column));
+}
+
+/// Rewrites references through a `pub use` alias to the item it re-exports, keeping the
+/// original references as well
+pub fn follow_re_exports(
+    references: &mut Vec<Reference>,
+    re_exports: &BTreeMap<Vec<String>, Vec<String>>,
+) {
+    let mut pending = references.clone();
+
+    // Each pass follows one more alias along a chain of re-exports
+    for _ in 0..MAX_ALIAS_DEPTH {
+        let followed = pending
+            .iter()
+            .filter_map(|reference| {
+                let (alias, target) = re_exports
+                    .iter()
+                    .find(|(alias, _)| reference.path.starts_with(alias))?;
+
+                Some(Reference {
+                    path: target
+                        .iter()
+                        .chain(&reference.path[alias.len()..])
+                        .cloned()
+                        .collect(),
+                    ..reference.clone()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        if followed.is_empty() {
+            break;
+        }
+
+        references.extend(followed.iter().cloned());
+        pending = followed;
+    }
+}
+
+pub fn write_text(
+    f: &mut impl Write,
+    usages: &[ExportUsages<'_>],
+    quiet: bool,
+) -> std::fmt::Result {
+    for usage in usages {
+        let packages = usage.packages();
+
+        write!(
+            f,
+            "{}:{}:{} {} {}: {} {}",
+            usage.export.file.display(),
+            usage.export.line,
+            usage.export.column,
+            usage.export.kind,
+            usage.path.join("::"),
+            usage.references.len(),
+            if usage.references.len() == 1 {
+                "reference"
+            } else {
+                "references"
+            }
+        )?;
+
+        if !packages.is_empty() {
+            write!(f, " from {}", Vec::from_iter(packages).join(", "))?;
+        }
+
+        writeln!(f)?;
+
+        if quiet {
+            continue;
+        }
+
+        for reference in &usage.references {
+            writeln!(
+                f,
+                "    {}:{}:{} {}",
+                reference.file.display(),
+                reference.line,
+                reference.column,
+                reference.package
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Top level of the `cargo purge usages --format json` output
+#[derive(Debug, Serialize)]
+pub struct JsonUsages<'a> {
+    pub version: u32,
+    /// Every export other than modules, ordered by path
+    pub exports: Vec<JsonExportUsages<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JsonExportUsages<'a> {
+    pub path: String,
+    pub kind: ItemKind,
+    /// File defining the export, relative to the primary workspace
+    pub file: &'a Path,
+    pub references: usize,
+    /// Packages referring to the export
+    pub packages: BTreeSet<&'a str>,
+    pub usages: Vec<JsonReference<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JsonReference<'a> {
+    pub package: &'a str,
+    /// Relative to the workspace the package belongs to
+    pub file: &'a Path,
+    /// 1-based line
+    pub line: usize,
+    /// 1-based column
+    pub column: usize,
+}
+
+impl<'a> JsonUsages<'a> {
+    pub fn new(usages: &[ExportUsages<'a>]) -> Self {
+        let exports = usages
+            .iter()
+            .map(|usage| JsonExportUsages {
+                path: usage.path.join("::"),
+                kind: usage.export.kind,
+                file: &usage.export.file,
+                references: usage.references.len(),
+                packages: usage.packages(),
+                usages: usage
+                    .references
+                    .iter()
+                    .map(|reference| JsonReference {
+                        package: &reference.package,
+                        file: &reference.file,
+                        line: reference.line,
+                        column: reference.column,
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Self {
+            version: USAGES_SCHEMA_VERSION,
+            exports,
+        }
+    }
+}
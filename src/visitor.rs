@@ -1,105 +1,465 @@
+use crate::export::{Export, Extent, ItemKind, Reference, Suppression};
 use crate::tree::Tree;
+use anyhow::{anyhow, Context, Result};
+use proc_macro2::{Span, TokenStream, TokenTree};
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 use std::path::PathBuf;
+use std::rc::Rc;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
 use syn::{
     visit::Visit, Item, ItemConst, ItemEnum, ItemFn, ItemForeignMod, ItemMacro, ItemMod,
-    ItemStatic, ItemStruct, ItemTrait, ItemTraitAlias, ItemType, Signature, Visibility,
+    ItemStatic, ItemStruct, ItemTrait, ItemTraitAlias, ItemType, Signature, Type, Visibility,
 };
-use syn::{Block, ExprPath, ItemUse, Stmt, UseTree};
+use syn::{
+    Attribute, Block, Expr, ExprLit, ExprPath, ExprStruct, ForeignItem, ForeignItemFn,
+    ForeignItemStatic, ImplItem, ItemExternCrate, ItemImpl, ItemUse, Lit, Macro, Meta, PatStruct,
+    PatTupleStruct, Path, Stmt, Token, TraitBound, TraitItem, TypePath, UseTree,
+};
+
+/// Whether code ends up in the shipped crate or is only compiled for tests, examples and benches
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Usage {
+    Production,
+    Test,
+}
+
+/// How `use some::module::*` is turned into imports
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GlobMode {
+    /// Everything in the module is considered used
+    Conservative,
+    /// Only the names referenced in the importing module are considered used
+    Precise,
+}
 
 pub struct Visitor {
     pub current_dir: PathBuf,
+    /// File containing the module currently being visited
+    pub current_file: PathBuf,
+    /// Contents of `current_file`, for finding `// purge:keep` comments
+    pub current_source: Rc<str>,
+    pub usage: Usage,
+    pub glob_mode: GlobMode,
+    /// Package the visited code belongs to
+    pub package: String,
+    /// Absolute path of the module currently being visited, starting with the crate name
+    pub module_path: Vec<String>,
+    /// Names of the items declared in the module currently being visited
+    local_names: BTreeSet<String>,
+    /// Names brought into scope by `use` declarations in the module currently being visited,
+    /// mapped to the absolute path they refer to
+    aliases: BTreeMap<String, Vec<String>>,
+    /// First segments of every path referenced in the module currently being visited
+    referenced_names: BTreeSet<String>,
+    /// Modules glob imported into the module currently being visited
+    globs: Vec<(Vec<String>, Usage)>,
+    /// Top level item currently being visited, which the paths referenced inside it belong to
+    current_item: Option<Vec<String>>,
+    /// Names referenced on their own that neither an item nor a `use` in the module declares,
+    /// along with the item referring to them. They may come from a glob import.
+    unresolved_names: Vec<(Vec<String>, String)>,
     pub exports_tree: Tree<String>,
+    /// Kind and location of everything in `exports_tree`, keyed by absolute path
+    pub export_info: BTreeMap<Vec<String>, Export>,
+    /// `#[macro_export]` macros, keyed by absolute path. They are exported from the crate root
+    /// wherever they are defined, so they join `exports_tree` once the crate root is visited.
+    exported_macros: BTreeMap<Vec<String>, Export>,
+    /// Items with a `pub(crate)`, `pub(super)` or `pub(in path)` visibility, which are exports
+    /// within their crate, keyed by absolute path
+    pub restricted: BTreeMap<Vec<String>, Export>,
     pub imports_tree: Tree<String>,
+    pub test_imports_tree: Tree<String>,
+    /// `pub use` aliases mapped to the absolute path of the item they re-export
+    pub re_exports: BTreeMap<Vec<String>, Vec<String>>,
+    /// Modules re-exporting everything from another module with `pub use module::*`
+    pub glob_re_exports: BTreeSet<(Vec<String>, Vec<String>)>,
+    /// Items and modules marked to never be reported
+    pub suppressions: Vec<Suppression>,
+    /// Every file visited, including those of submodules
+    pub visited_files: BTreeSet<PathBuf>,
+    /// Crates named by `extern crate` or a bare `use name;`, which the imports trees leave out
+    /// as nothing in them is used
+    pub external_crates: BTreeSet<String>,
+    /// Absolute paths every item refers to, keyed by the absolute path of the item. Modules are
+    /// items too, referring to what their `use` declarations import.
+    pub item_references: BTreeMap<Vec<String>, BTreeSet<Vec<String>>>,
+    /// Absolute paths every `pub` item names in its signature, keyed by the absolute path of the
+    /// item. The `pub` methods of an inherent impl block belong to the type they implement.
+    pub signatures: BTreeMap<Vec<String>, BTreeSet<Vec<String>>>,
+    /// Absolute path of every module visited
+    pub modules: BTreeSet<Vec<String>>,
+    /// Items used from outside of Rust, such as `#[no_mangle]` functions
+    pub roots: BTreeSet<Vec<String>>,
+    /// Items only compiled for tests
+    pub test_roots: BTreeSet<Vec<String>>,
+    /// Every path referring to an item outside the module it is written in
+    pub references: BTreeSet<Reference>,
+    /// First submodule file that couldn't be read or parsed, returned by `visit_file` once the
+    /// file declaring the submodule is done
+    error: Option<anyhow::Error>,
 }
 
 impl Visitor {
-    pub fn new(path: PathBuf) -> Self {
+    pub fn new(path: PathBuf, usage: Usage) -> Self {
         Self {
             current_dir: path,
+            current_file: PathBuf::new(),
+            current_source: Rc::from(""),
+            usage,
+            glob_mode: GlobMode::Conservative,
+            package: String::new(),
+            module_path: Vec::new(),
+            local_names: BTreeSet::new(),
+            aliases: BTreeMap::new(),
+            referenced_names: BTreeSet::new(),
+            globs: Vec::new(),
+            current_item: None,
+            unresolved_names: Vec::new(),
             exports_tree: Tree::new(),
+            export_info: BTreeMap::new(),
+            exported_macros: BTreeMap::new(),
+            restricted: BTreeMap::new(),
             imports_tree: Tree::new(),
+            test_imports_tree: Tree::new(),
+            re_exports: BTreeMap::new(),
+            glob_re_exports: BTreeSet::new(),
+            suppressions: Vec::new(),
+            visited_files: BTreeSet::new(),
+            external_crates: BTreeSet::new(),
+            item_references: BTreeMap::new(),
+            signatures: BTreeMap::new(),
+            modules: BTreeSet::new(),
+            roots: BTreeSet::new(),
+            test_roots: BTreeSet::new(),
+            references: BTreeSet::new(),
+            error: None,
         }
     }
 
-    pub fn visit_file(&mut self, path: PathBuf) {
-        let old_dir = self.current_dir.clone();
+    /// First segments of every path the visited code refers to, among which are the names of
+    /// the crates it depends on
+    pub fn crate_references(&self) -> BTreeSet<String> {
+        self.imports_tree
+            .0
+            .keys()
+            .chain(self.test_imports_tree.0.keys())
+            .chain(&self.external_crates)
+            .cloned()
+            .collect()
+    }
+
+    fn imports_mut(&mut self) -> &mut Tree<String> {
+        match self.usage {
+            Usage::Production => &mut self.imports_tree,
+            Usage::Test => &mut self.test_imports_tree,
+        }
+    }
+
+    pub fn visit_file(&mut self, module_name: String, path: PathBuf) -> Result<()> {
+        let file_content = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read `{}`", path.display()))?;
+        let syntax_tree = syn::parse_file(&file_content).map_err(|error| {
+            let start = error.span().start();
+            anyhow!(
+                "failed to parse `{}` at {}:{}: {}",
+                path.display(),
+                start.line,
+                start.column + 1,
+                error
+            )
+        })?;
+
+        self.visited_files.insert(path.clone());
+        self.current_file = path;
+        self.current_source = Rc::from(file_content);
+
+        // `#![cfg_attr(purge, keep)]` at the top of a module file covers the whole module
+        if self.usage == Usage::Production && is_kept(&syntax_tree.attrs) {
+            self.suppressions.push(Suppression {
+                path: self
+                    .module_path
+                    .iter()
+                    .chain([&module_name])
+                    .cloned()
+                    .collect(),
+                file: self.current_file.clone(),
+                line: 1,
+            });
+        }
+
+        self.visit_module(module_name, &syntax_tree.items);
+
+        self.error.take().map_or(Ok(()), Err)
+    }
+
+    fn visit_module(&mut self, module_name: String, items: &[Item]) {
         let mut old_tree = self.exports_tree.clone();
+        let old_names = self.local_names.clone();
+        let old_aliases = self.aliases.clone();
 
-        self.current_dir = path.parent().unwrap().into();
         self.exports_tree = Tree::new();
+        self.local_names = items.iter().filter_map(item_name).collect();
+        self.aliases = BTreeMap::new();
+        self.referenced_names = BTreeSet::new();
+        self.module_path.push(module_name.clone());
 
-        let file_content = fs::read_to_string(&path).unwrap();
-        let syntax_tree = syn::parse_file(&file_content).unwrap();
-
-        syn::visit::visit_file(self, &syntax_tree);
-
-        // todo - make this not awful
-        let module_name = if path.file_name().unwrap() == "lib.rs" {
-            path.parent()
-                .unwrap()
-                .parent()
-                .unwrap()
-                .file_name()
-                .unwrap()
-                .to_str()
-                .unwrap()
-                .to_owned()
-        } else if path.file_name().unwrap() == "mod.rs" {
-            path.parent()
-                .unwrap()
-                .file_name()
-                .unwrap()
-                .to_str()
-                .unwrap()
-                .to_owned()
-        } else {
-            path.file_stem().unwrap().to_str().unwrap().to_owned()
-        };
+        self.modules.insert(self.module_path.clone());
+        self.item_references
+            .entry(self.module_path.clone())
+            .or_default();
+
+        // A `#[cfg(test)]` module may do nothing but import what its tests use
+        if self.usage == Usage::Test {
+            self.test_roots.insert(self.module_path.clone());
+        }
+
+        // `use` declarations can build on each other in any order, so a second pass picks up
+        // aliases of aliases declared further down
+        for _ in 0..2 {
+            for item in items {
+                if let Item::Use(use_item) = item {
+                    self.record_aliases(use_item);
+                }
+            }
+        }
+
+        for item in items {
+            self.visit_item(item);
+        }
+
+        for name in &self.referenced_names {
+            let path = self
+                .module_path
+                .iter()
+                .chain([name])
+                .cloned()
+                .collect::<Vec<_>>();
+
+            if let Some(export) = self.export_info.get_mut(&path) {
+                export.used_locally = true;
+            }
+
+            if let Some(export) = self.restricted.get_mut(&path) {
+                export.used_locally = true;
+            }
+        }
+
+        // Names that don't resolve within the module may come from any module it glob imports
+        for (item, name) in std::mem::take(&mut self.unresolved_names) {
+            for (module, _) in &self.globs {
+                let path = module.iter().chain([&name]).cloned().collect();
+                self.item_references
+                    .entry(item.clone())
+                    .or_default()
+                    .insert(path);
+            }
+        }
+
+        self.expand_globs();
 
+        if self.module_path.len() == 1 {
+            for (path, export) in std::mem::take(&mut self.exported_macros) {
+                self.exports_tree.entry(path[1].clone()).or_insert(None);
+                self.export_info.insert(path, export);
+            }
+        }
+
+        self.module_path.pop();
         old_tree.insert(module_name, Some(self.exports_tree.clone()));
 
         self.exports_tree = old_tree;
-        self.current_dir = old_dir;
+        self.local_names = old_names;
+        self.aliases = old_aliases;
     }
-}
 
-fn process_use_tree(tree: &UseTree) -> Tree<String> {
-    match tree {
-        UseTree::Path(use_path) => {
-            let mut result = Tree::new();
-            let subtree = process_use_tree(&*use_path.tree);
-            result.insert(use_path.ident.to_string(), Some(subtree));
-            result
+    fn expand_globs(&mut self) {
+        for (module, usage) in std::mem::take(&mut self.globs) {
+            let imports = match usage {
+                Usage::Production => &mut self.imports_tree,
+                Usage::Test => &mut self.test_imports_tree,
+            };
+
+            // Test modules glob import their parent to reach whatever they test, which says
+            // nothing about the rest of it
+            match (self.glob_mode, usage) {
+                (GlobMode::Conservative, Usage::Production) => imports.insert_path(&module, None),
+                (GlobMode::Precise, _) | (_, Usage::Test) => {
+                    for name in self.referenced_names.difference(&self.local_names) {
+                        let path = module.iter().chain([name]).cloned().collect::<Vec<_>>();
+                        imports.insert_path(&path, None);
+                    }
+                }
+            }
         }
-        UseTree::Name(use_name) => {
-            let mut result = Tree::new();
-            result.insert(use_name.ident.to_string(), None);
-            result
+    }
+
+    /// Records the imports of a use tree against absolute paths, following any leading `crate`,
+    /// `self` and `super` segments
+    fn insert_use_tree(&mut self, prefix: Vec<String>, tree: Tree<String>) {
+        for (key, node) in tree {
+            let is_relative = matches!(key.as_str(), "crate" | "self" | "super");
+
+            let mut path = prefix.clone();
+            path.push(key);
+
+            match node {
+                Some(sub_tree) if is_relative => self.insert_use_tree(path, sub_tree),
+                // Importing a crate by name doesn't use anything in it
+                None if path.len() == 1 => {
+                    self.external_crates.extend(path);
+                }
+                node => {
+                    let path = self.resolve_path(&path);
+
+                    match &node {
+                        Some(sub_tree) => {
+                            for leaf in sub_tree.leaves() {
+                                self.record_reference(path.iter().chain(&leaf).cloned().collect());
+                            }
+                        }
+                        None => self.record_reference(path.clone()),
+                    }
+
+                    self.imports_mut().insert_path(&path, node);
+                }
+            }
         }
-        UseTree::Rename(use_rename) => {
-            let mut result = Tree::new();
-            result.insert(use_rename.rename.to_string(), None);
-            result
+    }
+
+    /// Records a reference from the item currently being visited, or from the module when
+    /// outside of any item
+    fn record_reference(&mut self, path: Vec<String>) {
+        let item = self
+            .current_item
+            .clone()
+            .unwrap_or_else(|| self.module_path.clone());
+
+        self.item_references.entry(item).or_default().insert(path);
+    }
+
+    /// Absolute path of a top level item, which references inside it are attributed to. Impl
+    /// blocks belong to the type they implement, and everything without a name belongs to the
+    /// module.
+    fn item_path(&self, item: &Item) -> Vec<String> {
+        let name = match item {
+            Item::Impl(ItemImpl { self_ty, .. }) => match &**self_ty {
+                Type::Path(TypePath { qself: None, path }) => {
+                    let path = self.resolve_syn_path(path);
+
+                    // A bare generic parameter doesn't resolve to anything
+                    if path.len() > 1 {
+                        return path;
+                    }
+
+                    None
+                }
+                _ => None,
+            },
+            Item::Macro(ItemMacro {
+                ident: Some(ident), ..
+            }) => {
+                if is_macro_exported(item) {
+                    return vec![self.module_path[0].clone(), ident.to_string()];
+                }
+
+                Some(ident.to_string())
+            }
+            item => item_name(item),
+        };
+
+        self.module_path.iter().cloned().chain(name).collect()
+    }
+
+    /// Records a path as an import when it is fully qualified or starts with an imported name
+    fn record_path(&mut self, path: &Path) {
+        let is_alias = match (&path.leading_colon, path.get_ident()) {
+            (None, Some(ident)) => self.aliases.contains_key(&ident.to_string()),
+            _ => false,
+        };
+
+        if path.segments.len() > 1 || is_alias {
+            let span = path.span();
+            let path = self.resolve_syn_path(path);
+            self.record_site(&path, span, false);
+            self.imports_mut().insert_path(&path, None);
         }
-        UseTree::Glob(_) => {
-            let mut result = Tree::new();
-            result.insert("*".to_string(), None);
-            result
+    }
+
+    /// Remembers where a path referring to an item outside the current module is written. Paths
+    /// inside functions are visited twice, which the set takes care of.
+    fn record_site(&mut self, path: &[String], span: Span, glob: bool) {
+        let start = span.start();
+
+        self.references.insert(Reference {
+            path: path.to_vec(),
+            package: self.package.clone(),
+            module: self.module_path.clone(),
+            file: self.current_file.clone(),
+            line: start.line,
+            column: start.column + 1,
+            test: self.usage == Usage::Test,
+            glob,
+        });
+    }
+
+    fn resolve_syn_path(&self, path: &Path) -> Vec<String> {
+        let segments = path
+            .segments
+            .iter()
+            .map(|segment| segment.ident.to_string())
+            .collect::<Vec<_>>();
+
+        // `::name` always refers to an external crate
+        if path.leading_colon.is_some() {
+            segments
+        } else {
+            self.resolve_path(&segments)
         }
-        UseTree::Group(use_group) => {
-            let mut result = Tree::new();
-            for tree in &use_group.items {
-                let subtree = process_use_tree(tree);
-                result.extend(subtree);
+    }
+
+    /// Turns a path written in the current module into an absolute one starting with a crate name
+    fn resolve_path(&self, path: &[String]) -> Vec<String> {
+        let Some(first) = path.first() else {
+            return Vec::new();
+        };
+
+        match first.as_str() {
+            "crate" => self.module_path[..1]
+                .iter()
+                .chain(&path[1..])
+                .cloned()
+                .collect(),
+            "self" => self.module_path.iter().chain(&path[1..]).cloned().collect(),
+            "super" => {
+                let supers = path
+                    .iter()
+                    .take_while(|segment| *segment == "super")
+                    .count();
+                let parent_len = self.module_path.len().saturating_sub(supers).max(1);
+
+                self.module_path[..parent_len]
+                    .iter()
+                    .chain(&path[supers..])
+                    .cloned()
+                    .collect()
             }
-            result
+            name if self.aliases.contains_key(name) => self.aliases[name]
+                .iter()
+                .chain(&path[1..])
+                .cloned()
+                .collect(),
+            name if self.local_names.contains(name) => {
+                self.module_path.iter().chain(path).cloned().collect()
+            }
+            _ => path.to_vec(),
         }
     }
-}
 
-impl<'ast> Visit<'ast> for Visitor {
-    fn visit_item(&mut self, i: &'ast Item) {
+    fn visit_exported_item(&mut self, i: &Item) {
+        let mut module_file = None;
+
         let item = match i {
             Item::Struct(ItemStruct {
                 vis: Visibility::Public(_),
@@ -136,14 +496,16 @@ impl<'ast> Visit<'ast> for Visitor {
                 ident,
                 ..
             }) => Some(ident.to_string()),
-            Item::ForeignMod(ItemForeignMod {
-                abi: _, items: _, ..
-            }) => {
-                unimplemented!()
-            }
-            Item::Macro(ItemMacro { mac, .. }) => {
-                Some(mac.path.segments.last().unwrap().ident.to_string())
+            Item::ForeignMod(ItemForeignMod { items, .. }) => {
+                for item in items {
+                    self.visit_foreign_item(item);
+                }
+
+                None
             }
+            Item::Macro(ItemMacro {
+                ident: Some(ident), ..
+            }) if is_macro_exported(i) => Some(ident.to_string()),
             Item::Fn(ItemFn {
                 vis,
                 sig: Signature { ident, .. },
@@ -159,6 +521,7 @@ impl<'ast> Visit<'ast> for Visitor {
                 }
             }
             Item::Mod(ItemMod {
+                attrs,
                 vis,
                 ident,
                 content,
@@ -166,24 +529,76 @@ impl<'ast> Visit<'ast> for Visitor {
             }) => {
                 let name = ident.to_string();
 
-                if content.is_none() {
-                    let mod_dir = self.current_dir.join(&name);
-                    let mod_file = mod_dir.join("mod.rs");
-                    let alt_mod_file = self.current_dir.join(format!("{}.rs", &name));
+                let mut mod_dir = self.current_dir.join(&name);
+                let mod_file = mod_dir.join("mod.rs");
+                let alt_mod_file = self.current_dir.join(format!("{}.rs", &name));
 
-                    let mut visitor = Visitor::new(mod_dir);
+                // `#[path = "file.rs"]` is relative to the directory of the declaring file, and
+                // the file it points to looks for submodules next to itself
+                let path_file = path_attr(attrs).and_then(|path| {
+                    let file = self.current_file.parent()?.join(path);
+                    mod_dir = file.parent()?.into();
+                    Some(file)
+                });
 
-                    if mod_file.exists() {
-                        visitor.visit_file(mod_file);
-                    } else if alt_mod_file.exists() {
-                        visitor.visit_file(alt_mod_file);
-                    }
+                // Both `name/mod.rs` and `name.rs` look for their own submodules in `name/`
+                let mut visitor = Visitor::new(mod_dir, self.usage);
+                visitor.glob_mode = self.glob_mode;
+                visitor.package = self.package.clone();
+                visitor.module_path = self.module_path.clone();
+                visitor.current_file = self.current_file.clone();
+                visitor.current_source = self.current_source.clone();
 
-                    if matches!(vis, Visibility::Public(_)) {
-                        self.exports_tree.extend(visitor.exports_tree);
-                    }
+                let result = if let Some((_, items)) = content {
+                    visitor.visit_module(name.clone(), items);
+                    Ok(())
+                } else if let Some(path_file) = path_file.filter(|file| file.exists()) {
+                    module_file = Some(path_file.clone());
+                    visitor.visit_file(name.clone(), path_file)
+                } else if mod_file.exists() {
+                    module_file = Some(mod_file.clone());
+                    visitor.visit_file(name.clone(), mod_file)
+                } else if alt_mod_file.exists() {
+                    module_file = Some(alt_mod_file.clone());
+                    visitor.visit_file(name.clone(), alt_mod_file)
+                } else {
+                    Ok(())
+                };
 
-                    self.imports_tree.extend(visitor.imports_tree);
+                // Inline modules leave the errors of their submodule files to this one
+                if let Some(error) = result.err().or(visitor.error.take()) {
+                    self.error.get_or_insert(error);
+                }
+
+                if matches!(vis, Visibility::Public(_)) {
+                    self.exports_tree.extend(visitor.exports_tree);
+                    self.export_info.extend(visitor.export_info);
+                }
+
+                self.exported_macros.extend(visitor.exported_macros);
+
+                self.restricted.extend(visitor.restricted);
+                self.imports_tree.extend(visitor.imports_tree);
+                self.test_imports_tree.extend(visitor.test_imports_tree);
+                self.re_exports.extend(visitor.re_exports);
+                self.glob_re_exports.extend(visitor.glob_re_exports);
+                self.suppressions.extend(visitor.suppressions);
+                self.visited_files.extend(visitor.visited_files);
+                self.external_crates.extend(visitor.external_crates);
+                self.modules.extend(visitor.modules);
+                self.roots.extend(visitor.roots);
+                self.test_roots.extend(visitor.test_roots);
+                self.references.extend(visitor.references);
+
+                for (item, references) in visitor.item_references {
+                    self.item_references
+                        .entry(item)
+                        .or_default()
+                        .extend(references);
+                }
+
+                for (item, paths) in visitor.signatures {
+                    self.signatures.entry(item).or_default().extend(paths);
                 }
 
                 if matches!(vis, Visibility::Public(_)) {
@@ -192,23 +607,624 @@ impl<'ast> Visit<'ast> for Visitor {
                     None
                 }
             }
-            Item::Use(use_item) => {
-                self.visit_item_use(use_item);
-                None
+            _ => None,
+        };
+
+        // Test-only items are invisible to other crates, so they can't be exports
+        if let (Some(name), Some(kind), Usage::Production) = (item, ItemKind::of(i), self.usage) {
+            let (vis, span) = match item_visibility(i) {
+                Some(vis @ Visibility::Public(_)) => (vis, vis.span()),
+                _ => (&Visibility::Inherited, i.span()),
+            };
+
+            let path = self.item_path(i);
+            let start = item_attrs(i).first().map_or(i.span(), |attr| attr.span());
+
+            let mut export = Export::new(kind, vis, self.current_file.clone(), span);
+            export.extent = Some(Extent::new(start, i.span()));
+            export.module_file = module_file;
+
+            if kind == ItemKind::Macro {
+                self.exported_macros.insert(path, export);
+            } else {
+                self.export_info.insert(path, export);
+                self.exports_tree.entry(name).or_insert(None);
             }
+        }
+
+        if let (Some(name), Some(vis @ Visibility::Restricted(_)), Usage::Production) =
+            (item_name(i), item_visibility(i), self.usage)
+        {
+            self.visit_restricted_item(i, name, vis);
+        }
+
+        // Module contents have already been visited by their own visitor
+        if !matches!(i, Item::Mod(_)) {
+            syn::visit::visit_item(self, i);
+        }
+    }
+
+    /// Records the paths a `pub` item names in its signature, which other crates see along with it
+    fn record_signature(&mut self, i: &Item) {
+        let mut paths = SignaturePaths::default();
+        let is_pub = |vis: &Visibility| matches!(vis, Visibility::Public(_));
+
+        match i {
+            Item::Fn(item) if is_pub(&item.vis) => paths.visit_signature(&item.sig),
+            Item::Struct(item) if is_pub(&item.vis) => {
+                paths.visit_generics(&item.generics);
+
+                for field in item.fields.iter().filter(|field| is_pub(&field.vis)) {
+                    paths.visit_type(&field.ty);
+                }
+            }
+            Item::Enum(item) if is_pub(&item.vis) => {
+                paths.visit_generics(&item.generics);
+
+                for field in item.variants.iter().flat_map(|variant| &variant.fields) {
+                    paths.visit_type(&field.ty);
+                }
+            }
+            Item::Type(item) if is_pub(&item.vis) => {
+                paths.visit_generics(&item.generics);
+                paths.visit_type(&item.ty);
+            }
+            Item::Const(item) if is_pub(&item.vis) => paths.visit_type(&item.ty),
+            Item::Static(item) if is_pub(&item.vis) => paths.visit_type(&item.ty),
+            Item::Trait(item) if is_pub(&item.vis) => {
+                paths.visit_generics(&item.generics);
+
+                for bound in &item.supertraits {
+                    paths.visit_type_param_bound(bound);
+                }
+
+                for trait_item in &item.items {
+                    match trait_item {
+                        TraitItem::Fn(trait_fn) => paths.visit_signature(&trait_fn.sig),
+                        TraitItem::Type(trait_type) => {
+                            for bound in &trait_type.bounds {
+                                paths.visit_type_param_bound(bound);
+                            }
+                        }
+                        TraitItem::Const(trait_const) => paths.visit_type(&trait_const.ty),
+                        _ => {}
+                    }
+                }
+            }
+            // Trait impls can only be as public as the trait and the type already are
+            Item::Impl(item) if item.trait_.is_none() => {
+                for impl_item in &item.items {
+                    if let ImplItem::Fn(method) = impl_item {
+                        if is_pub(&method.vis) {
+                            paths.visit_signature(&method.sig);
+                        }
+                    }
+                }
+            }
+            _ => return,
+        }
+
+        let paths = paths
+            .0
+            .iter()
+            .map(|path| self.resolve_syn_path(path))
+            .collect::<BTreeSet<_>>();
+
+        if !paths.is_empty() {
+            self.signatures
+                .entry(self.item_path(i))
+                .or_default()
+                .extend(paths);
+        }
+    }
+
+    /// Records a `pub fn` or `pub static` declared in an `extern` block, which is an export like
+    /// any other function or static
+    fn visit_foreign_item(&mut self, i: &ForeignItem) {
+        let (kind, vis, ident, attrs) = match i {
+            ForeignItem::Fn(ForeignItemFn {
+                vis,
+                sig: Signature { ident, .. },
+                attrs,
+                ..
+            }) => (ItemKind::Fn, vis, ident, attrs),
+            ForeignItem::Static(ForeignItemStatic {
+                vis, ident, attrs, ..
+            }) => (ItemKind::Static, vis, ident, attrs),
             _ => return,
         };
 
-        if let Some(name) = item {
+        if !matches!(vis, Visibility::Public(_)) || self.usage != Usage::Production {
+            return;
+        }
+
+        let name = ident.to_string();
+        let path = self
+            .module_path
+            .iter()
+            .cloned()
+            .chain([name.clone()])
+            .collect();
+        let start = attrs.first().map_or(i.span(), |attr| attr.span());
+
+        let mut export = Export::new(kind, vis, self.current_file.clone(), vis.span());
+        export.extent = Some(Extent::new(start, i.span()));
+
+        self.export_info.insert(path, export);
+        self.exports_tree.entry(name).or_insert(None);
+    }
+
+    /// Records an item only visible inside its crate. Modules are left out, as what matters is
+    /// whether anything uses the items inside them.
+    fn visit_restricted_item(&mut self, i: &Item, name: String, vis: &Visibility) {
+        let Some(kind) = ItemKind::of(i).filter(|kind| *kind != ItemKind::Mod) else {
+            return;
+        };
+
+        let path = self.module_path.iter().cloned().chain([name]).collect();
+        let start = item_attrs(i).first().map_or(i.span(), |attr| attr.span());
+
+        let mut export = Export::new(kind, vis, self.current_file.clone(), vis.span());
+        export.extent = Some(Extent::new(start, i.span()));
+
+        self.restricted.insert(path, export);
+    }
+
+    /// Remembers the names a `use` declaration brings into scope, so later paths starting with
+    /// them can be followed back to the original item
+    fn record_aliases(&mut self, i: &ItemUse) {
+        let mut use_paths = Vec::new();
+        flatten_use_tree(&i.tree, Vec::new(), &mut use_paths);
+
+        for (path, name) in use_paths {
+            if name != "*" {
+                // `use foo::foo;` mustn't resolve through the alias it is declaring
+                self.aliases.remove(&name);
+                let target = self.resolve_path(&path);
+                self.aliases.insert(name, target);
+            }
+        }
+    }
+
+    fn visit_re_export(&mut self, i: &ItemUse) {
+        let mut use_paths = Vec::new();
+        flatten_use_tree(&i.tree, Vec::new(), &mut use_paths);
+
+        for (path, name) in use_paths {
+            // Glob re-exports don't introduce a name of their own
+            if name == "*" {
+                let target = self.resolve_path(&path[..path.len() - 1]);
+                self.glob_re_exports
+                    .insert((self.module_path.clone(), target));
+                continue;
+            }
+
+            let alias = self
+                .module_path
+                .iter()
+                .cloned()
+                .chain([name.clone()])
+                .collect::<Vec<_>>();
+            let target = self.resolve_path(&path);
+            let export = Export::new(
+                ItemKind::Use,
+                &i.vis,
+                self.current_file.clone(),
+                i.vis.span(),
+            );
+
+            self.export_info.insert(alias.clone(), export);
+            self.re_exports.insert(alias, target);
             self.exports_tree.entry(name).or_insert(None);
         }
+    }
+}
+
+/// Every identifier in a token stream, including inside groups
+fn token_idents(tokens: TokenStream) -> Vec<String> {
+    tokens
+        .into_iter()
+        .flat_map(|token| match token {
+            TokenTree::Ident(ident) => vec![ident.to_string()],
+            TokenTree::Group(group) => token_idents(group.stream()),
+            TokenTree::Punct(_) | TokenTree::Literal(_) => Vec::new(),
+        })
+        .collect()
+}
+
+fn item_visibility(item: &Item) -> Option<&Visibility> {
+    match item {
+        Item::Const(i) => Some(&i.vis),
+        Item::Enum(i) => Some(&i.vis),
+        Item::ExternCrate(i) => Some(&i.vis),
+        Item::Fn(i) => Some(&i.vis),
+        Item::Mod(i) => Some(&i.vis),
+        Item::Static(i) => Some(&i.vis),
+        Item::Struct(i) => Some(&i.vis),
+        Item::Trait(i) => Some(&i.vis),
+        Item::TraitAlias(i) => Some(&i.vis),
+        Item::Type(i) => Some(&i.vis),
+        Item::Union(i) => Some(&i.vis),
+        Item::Use(i) => Some(&i.vis),
+        _ => None,
+    }
+}
+
+fn item_name(item: &Item) -> Option<String> {
+    match item {
+        Item::Const(i) => Some(i.ident.to_string()),
+        Item::Enum(i) => Some(i.ident.to_string()),
+        Item::ExternCrate(i) => Some(i.ident.to_string()),
+        Item::Fn(i) => Some(i.sig.ident.to_string()),
+        Item::Mod(i) => Some(i.ident.to_string()),
+        Item::Static(i) => Some(i.ident.to_string()),
+        Item::Struct(i) => Some(i.ident.to_string()),
+        Item::Trait(i) => Some(i.ident.to_string()),
+        Item::TraitAlias(i) => Some(i.ident.to_string()),
+        Item::Type(i) => Some(i.ident.to_string()),
+        Item::Union(i) => Some(i.ident.to_string()),
+        _ => None,
+    }
+}
+
+fn item_attrs(item: &Item) -> &[Attribute] {
+    match item {
+        Item::Const(i) => &i.attrs,
+        Item::Enum(i) => &i.attrs,
+        Item::ExternCrate(i) => &i.attrs,
+        Item::Fn(i) => &i.attrs,
+        Item::ForeignMod(i) => &i.attrs,
+        Item::Impl(i) => &i.attrs,
+        Item::Macro(i) => &i.attrs,
+        Item::Mod(i) => &i.attrs,
+        Item::Static(i) => &i.attrs,
+        Item::Struct(i) => &i.attrs,
+        Item::Trait(i) => &i.attrs,
+        Item::TraitAlias(i) => &i.attrs,
+        Item::Type(i) => &i.attrs,
+        Item::Union(i) => &i.attrs,
+        Item::Use(i) => &i.attrs,
+        _ => &[],
+    }
+}
+
+// `#[test]`, `#[cfg(test)]` and `#[cfg(all(test, ...))]` items only exist in test builds
+fn is_test_only(attrs: &[Attribute]) -> bool {
+    fn requires_test(meta: &Meta) -> bool {
+        match meta {
+            Meta::Path(path) => path.is_ident("test"),
+            Meta::List(list) if list.path.is_ident("all") => list
+                .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+                .map(|metas| metas.iter().any(requires_test))
+                .unwrap_or(false),
+            _ => false,
+        }
+    }
+
+    attrs.iter().any(|attr| {
+        attr.path().is_ident("test")
+            || (attr.path().is_ident("cfg")
+                && attr
+                    .parse_args::<Meta>()
+                    .map(|meta| requires_test(&meta))
+                    .unwrap_or(false))
+    })
+}
+
+/// The file a `#[path = "..."]` attribute points a module declaration to
+fn path_attr(attrs: &[Attribute]) -> Option<String> {
+    attrs.iter().find_map(|attr| match &attr.meta {
+        Meta::NameValue(name_value) if name_value.path.is_ident("path") => {
+            match &name_value.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(path),
+                    ..
+                }) => Some(path.value()),
+                _ => None,
+            }
+        }
+        _ => None,
+    })
+}
+
+/// Whether an item is a `macro_rules!` definition marked `#[macro_export]`. Other macro items are
+/// invocations, and macros without the attribute can't be used outside their crate.
+fn is_macro_exported(item: &Item) -> bool {
+    match item {
+        Item::Macro(ItemMacro {
+            attrs,
+            ident: Some(_),
+            mac,
+            ..
+        }) => {
+            mac.path.is_ident("macro_rules")
+                && attrs
+                    .iter()
+                    .any(|attr| attr.path().is_ident("macro_export"))
+        }
+        _ => false,
+    }
+}
+
+/// Items called without any path referring to them: `#[no_mangle]` and `#[export_name = "..."]`
+/// items are called from outside of Rust, and procedural macros by the compiler
+fn is_called_externally(attrs: &[Attribute]) -> bool {
+    let is_symbol = |path: &Path| {
+        [
+            "no_mangle",
+            "export_name",
+            "proc_macro",
+            "proc_macro_attribute",
+            "proc_macro_derive",
+        ]
+        .iter()
+        .any(|name| path.is_ident(name))
+    };
+
+    attrs.iter().any(|attr| {
+        is_symbol(attr.path())
+            // Edition 2024 spells them `#[unsafe(no_mangle)]`
+            || (attr.path().is_ident("unsafe")
+                && attr
+                    .parse_args::<Meta>()
+                    .map(|meta| is_symbol(meta.path()))
+                    .unwrap_or(false))
+    })
+}
+
+/// `#[cfg_attr(purge, ...)]` marks an item as intentionally exported. `purge` is never set, so
+/// the attribute doesn't change what gets compiled.
+fn is_kept(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        attr.path().is_ident("cfg_attr")
+            && attr
+                .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+                .map(|metas| matches!(metas.first(), Some(Meta::Path(path)) if path.is_ident("purge")))
+                .unwrap_or(false)
+    })
+}
+
+/// Whether a `// purge:keep` comment trails the first line of an item or sits in the comments
+/// directly above it
+fn has_keep_comment(source: &str, line: usize) -> bool {
+    let lines = source.lines().collect::<Vec<_>>();
+    let Some(first_line) = line.checked_sub(1).and_then(|index| lines.get(index)) else {
+        return false;
+    };
+
+    let is_keep = |line: &str| {
+        line.find("//")
+            .is_some_and(|start| line[start..].contains("purge:keep"))
+    };
+
+    is_keep(first_line)
+        || lines[..line - 1]
+            .iter()
+            .rev()
+            .map(|line| line.trim_start())
+            .take_while(|line| line.starts_with("//"))
+            .any(is_keep)
+}
+
+/// Collects every path a use tree brings into scope, along with the name it is bound to
+fn flatten_use_tree(tree: &UseTree, prefix: Vec<String>, paths: &mut Vec<(Vec<String>, String)>) {
+    match tree {
+        UseTree::Path(use_path) => {
+            let mut prefix = prefix;
+            prefix.push(use_path.ident.to_string());
+            flatten_use_tree(&use_path.tree, prefix, paths);
+        }
+        UseTree::Name(use_name) if use_name.ident == "self" => {
+            if let Some(name) = prefix.last().cloned() {
+                paths.push((prefix, name));
+            }
+        }
+        UseTree::Name(use_name) => {
+            let name = use_name.ident.to_string();
+            let mut path = prefix;
+            path.push(name.clone());
+            paths.push((path, name));
+        }
+        UseTree::Rename(use_rename) => {
+            let mut path = prefix;
+            path.push(use_rename.ident.to_string());
+            paths.push((path, use_rename.rename.to_string()));
+        }
+        UseTree::Glob(_) => {
+            let mut path = prefix;
+            path.push("*".to_string());
+            paths.push((path, "*".to_string()));
+        }
+        UseTree::Group(use_group) => {
+            for tree in &use_group.items {
+                flatten_use_tree(tree, prefix.clone(), paths);
+            }
+        }
+    }
+}
+
+fn process_use_tree(tree: &UseTree) -> Tree<String> {
+    match tree {
+        UseTree::Path(use_path) => {
+            let mut result = Tree::new();
+            let subtree = process_use_tree(&use_path.tree);
+            result.insert(use_path.ident.to_string(), Some(subtree));
+            result
+        }
+        UseTree::Name(use_name) => {
+            let mut result = Tree::new();
+            result.insert(use_name.ident.to_string(), None);
+            result
+        }
+        UseTree::Rename(use_rename) => {
+            let mut result = Tree::new();
+            result.insert(use_rename.ident.to_string(), None);
+            result
+        }
+        // Globs can only be expanded once the whole module has been visited
+        UseTree::Glob(_) => Tree::new(),
+        UseTree::Group(use_group) => {
+            let mut result = Tree::new();
+            for tree in &use_group.items {
+                let subtree = process_use_tree(tree);
+                result.extend(subtree);
+            }
+            result
+        }
+    }
+}
+
+/// Every path in a signature, including those in generic arguments and bounds
+#[derive(Default)]
+struct SignaturePaths(Vec<Path>);
+
+impl<'ast> Visit<'ast> for SignaturePaths {
+    fn visit_path(&mut self, i: &'ast Path) {
+        self.0.push(i.clone());
+        syn::visit::visit_path(self, i);
+    }
+}
+
+impl<'ast> Visit<'ast> for Visitor {
+    fn visit_item(&mut self, i: &'ast Item) {
+        let usage = self.usage;
+
+        if is_test_only(item_attrs(i)) {
+            self.usage = Usage::Test;
+        }
+
+        let current_item = self.current_item.clone();
+
+        // Module contents are items of their own, and nested items belong to the outer one
+        if self.current_item.is_none() && !matches!(i, Item::Mod(_)) {
+            let path = self.item_path(i);
+
+            if path != self.module_path {
+                // Whatever the module imports is used once anything in it is
+                self.item_references
+                    .entry(path.clone())
+                    .or_default()
+                    .insert(self.module_path.clone());
+
+                if self.usage == Usage::Test {
+                    self.test_roots.insert(path.clone());
+                } else if is_called_externally(item_attrs(i)) {
+                    self.roots.insert(path.clone());
+                }
+            }
+
+            self.current_item = Some(path);
+        }
+
+        if let (Some(name), Usage::Production) = (item_name(i), self.usage) {
+            let line = match item_attrs(i).first() {
+                Some(attr) => attr.span().start().line,
+                None => i.span().start().line,
+            };
+
+            if is_kept(item_attrs(i)) || has_keep_comment(&self.current_source, line) {
+                self.suppressions.push(Suppression {
+                    path: self.module_path.iter().cloned().chain([name]).collect(),
+                    file: self.current_file.clone(),
+                    line,
+                });
+            }
+        }
+
+        if self.usage == Usage::Production {
+            self.record_signature(i);
+        }
+
+        self.visit_exported_item(i);
 
-        syn::visit::visit_item(self, i);
+        self.current_item = current_item;
+        self.usage = usage;
     }
 
     fn visit_item_use(&mut self, i: &'ast ItemUse) {
+        // Re-exports don't use the item themselves, they only give it another public path
+        if let (Visibility::Public(_), Usage::Production) = (&i.vis, self.usage) {
+            self.visit_re_export(i);
+            self.record_aliases(i);
+            return;
+        }
+
         let tree = process_use_tree(&i.tree);
-        self.imports_tree.extend(tree);
+        self.insert_use_tree(Vec::new(), tree);
+
+        let mut use_paths = Vec::new();
+        flatten_use_tree(&i.tree, Vec::new(), &mut use_paths);
+
+        for (mut path, name) in use_paths {
+            let glob = name == "*";
+
+            if glob {
+                path.pop();
+                self.globs.push((self.resolve_path(&path), self.usage));
+            }
+
+            // Importing a crate by name doesn't refer to anything in it
+            if path.len() > 1 {
+                let path = self.resolve_path(&path);
+                self.record_site(&path, i.span(), glob);
+            }
+        }
+
+        self.record_aliases(i);
+    }
+
+    fn visit_item_extern_crate(&mut self, i: &'ast ItemExternCrate) {
+        if i.ident != "self" {
+            self.external_crates.insert(i.ident.to_string());
+        }
+
+        syn::visit::visit_item_extern_crate(self, i);
+    }
+
+    fn visit_attribute(&mut self, i: &'ast Attribute) {
+        // Derived traits are only known to the compiler as the arguments of `#[derive(...)]`
+        if i.path().is_ident("derive") {
+            if let Ok(paths) = i.parse_args_with(Punctuated::<Path, Token![,]>::parse_terminated) {
+                for path in &paths {
+                    self.record_path(path);
+                    self.visit_path(path);
+                }
+            }
+        } else {
+            self.record_path(i.path());
+        }
+
+        syn::visit::visit_attribute(self, i);
+    }
+
+    fn visit_macro(&mut self, i: &'ast Macro) {
+        self.record_path(&i.path);
+
+        // Macro arguments aren't parsed, but any name in them may refer to an item
+        self.referenced_names.extend(token_idents(i.tokens.clone()));
+
+        syn::visit::visit_macro(self, i);
+    }
+
+    fn visit_path(&mut self, i: &'ast Path) {
+        if let (None, Some(segment)) = (&i.leading_colon, i.segments.first()) {
+            self.referenced_names.insert(segment.ident.to_string());
+        }
+
+        let path = self.resolve_syn_path(i);
+
+        match (&i.leading_colon, path.as_slice()) {
+            (None, [name]) => {
+                let item = self
+                    .current_item
+                    .clone()
+                    .unwrap_or_else(|| self.module_path.clone());
+                self.unresolved_names.push((item, name.clone()));
+            }
+            _ => self.record_reference(path),
+        }
+
+        syn::visit::visit_path(self, i);
     }
 
     fn visit_block(&mut self, i: &'ast Block) {
@@ -222,37 +1238,41 @@ impl<'ast> Visit<'ast> for Visitor {
     }
 
     fn visit_expr_path(&mut self, i: &'ast ExprPath) {
-        let segments = &i.path.segments;
-        let len = segments.len();
+        self.record_path(&i.path);
+        syn::visit::visit_expr_path(self, i);
+    }
 
-        // Capture fully qualified calls as imports
-        if len > 1 {
-            let mut current_tree = &mut self.imports_tree;
+    fn visit_expr_struct(&mut self, i: &'ast ExprStruct) {
+        self.record_path(&i.path);
+        syn::visit::visit_expr_struct(self, i);
+    }
 
-            for (index, segment) in segments.iter().enumerate() {
-                let segment_name = segment.ident.to_string();
+    fn visit_type_path(&mut self, i: &'ast TypePath) {
+        self.record_path(&i.path);
+        syn::visit::visit_type_path(self, i);
+    }
 
-                if index == len - 1 {
-                    // Last segment, insert as None to indicate the end of the import
-                    current_tree.0.entry(segment_name).or_insert(None);
-                } else {
-                    // Intermediate segments, insert as Some(Tree)
-                    current_tree = current_tree
-                        .0
-                        .entry(segment_name)
-                        .and_modify(|val| {
-                            if val.is_none() {
-                                *val = Some(Tree::new())
-                            }
-                        })
-                        .or_insert_with(|| Some(Tree::new()))
-                        .as_mut()
-                        .unwrap();
-                }
-            }
+    fn visit_trait_bound(&mut self, i: &'ast TraitBound) {
+        self.record_path(&i.path);
+        syn::visit::visit_trait_bound(self, i);
+    }
+
+    fn visit_item_impl(&mut self, i: &'ast ItemImpl) {
+        if let Some((_, path, _)) = &i.trait_ {
+            self.record_path(path);
         }
 
-        syn::visit::visit_expr_path(self, i);
+        syn::visit::visit_item_impl(self, i);
+    }
+
+    fn visit_pat_struct(&mut self, i: &'ast PatStruct) {
+        self.record_path(&i.path);
+        syn::visit::visit_pat_struct(self, i);
+    }
+
+    fn visit_pat_tuple_struct(&mut self, i: &'ast PatTupleStruct) {
+        self.record_path(&i.path);
+        syn::visit::visit_pat_tuple_struct(self, i);
     }
 }
 
@@ -264,8 +1284,8 @@ mod test {
     #[test]
     fn it_correctly_gets_public_exports() {
         let path = PathBuf::from("test_workspaces/workspace_1/lib/package_1/src/lib.rs");
-        let mut visitor = Visitor::new(path.parent().unwrap().into());
-        visitor.visit_file(PathBuf::from(path));
+        let mut visitor = Visitor::new(path.parent().unwrap().into(), Usage::Production);
+        visitor.visit_file("package_1".to_string(), path).unwrap();
 
         let exports = serde_json::to_value(visitor.exports_tree).unwrap();
 
@@ -294,10 +1314,11 @@ mod test {
     fn it_correctly_gets_all_imports() {
         let path = PathBuf::from("test_workspaces/workspace_1/lib/package_2/src/lib.rs");
 
-        let mut visitor = Visitor::new(path.parent().unwrap().into());
-        visitor.visit_file(PathBuf::from(path));
+        let mut visitor = Visitor::new(path.parent().unwrap().into(), Usage::Production);
+        visitor.visit_file("package_2".to_string(), path).unwrap();
 
         let imports = serde_json::to_value(visitor.imports_tree).unwrap();
+        let test_imports = serde_json::to_value(visitor.test_imports_tree).unwrap();
 
         assert_eq!(
             imports,
@@ -307,16 +1328,117 @@ mod test {
                     "public_hello_2": null,
                     "public_hello_3": null,
                     "public_module": {
-                        "public_hello": null,
                         "public": {
                             "public_hello": null,
                         },
                     },
                 },
-                "super": {
-                    "*": null
-                }
+            })
+        );
+        assert_eq!(
+            test_imports,
+            json!({
+                "package_1": {
+                    "public_module": {
+                        "public_hello": null,
+                    },
+                },
             })
         )
     }
+
+    #[test]
+    fn it_resolves_relative_paths_against_the_current_module() {
+        let path = PathBuf::from("test_workspaces/workspace_4/facade/src/lib.rs");
+
+        let mut visitor = Visitor::new(path.parent().unwrap().into(), Usage::Production);
+        visitor.visit_file("facade".to_string(), path).unwrap();
+
+        let imports = serde_json::to_value(visitor.imports_tree).unwrap();
+
+        assert_eq!(
+            imports,
+            json!({
+                "facade": {
+                    "api": {
+                        "Widget": null,
+                        "used_directly": null,
+                    },
+                    "inner": {
+                        "Thing": null,
+                        "hidden": null,
+                    },
+                    "relative": {
+                        "nested": {
+                            "helper": null,
+                        },
+                    },
+                    "tools": {
+                        "glob_used": null,
+                    },
+                },
+            })
+        )
+    }
+
+    #[test]
+    fn it_follows_renamed_imports_back_to_the_original_path() {
+        let path = PathBuf::from("test_workspaces/workspace_4/consumer/src/main.rs");
+
+        let mut visitor = Visitor::new(path.parent().unwrap().into(), Usage::Production);
+        visitor.visit_file("consumer".to_string(), path).unwrap();
+
+        let imports = serde_json::to_value(visitor.imports_tree).unwrap();
+
+        assert_eq!(
+            imports,
+            json!({
+                "facade": {
+                    "Thing": null,
+                    "api": {
+                        "Widget": null,
+                        "used_directly": null,
+                    },
+                    "prelude": null,
+                    "renamed": null,
+                },
+            })
+        )
+    }
+
+    #[test]
+    fn it_records_re_exports_against_their_absolute_paths() {
+        let path = PathBuf::from("test_workspaces/workspace_4/facade/src/lib.rs");
+
+        let mut visitor = Visitor::new(path.parent().unwrap().into(), Usage::Production);
+        visitor.visit_file("facade".to_string(), path).unwrap();
+
+        let re_exports = visitor
+            .re_exports
+            .into_iter()
+            .map(|(alias, target)| (alias.join("::"), target.join("::")))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            re_exports,
+            vec![
+                (
+                    "facade::Thing".to_string(),
+                    "facade::inner::Thing".to_string()
+                ),
+                (
+                    "facade::Widget".to_string(),
+                    "facade::api::Widget".to_string()
+                ),
+                (
+                    "facade::renamed".to_string(),
+                    "facade::inner::hidden".to_string()
+                ),
+                (
+                    "facade::unused_function".to_string(),
+                    "facade::api::unused_function".to_string()
+                ),
+            ]
+        );
+    }
 }
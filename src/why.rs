@@ -0,0 +1,252 @@
+use crate::config::PurgeConfig;
+use crate::export::{ItemKind, Reference};
+use crate::report::{Category, Report};
+use crate::usages::references_to;
+use crate::visitor::GlobMode;
+use anyhow::{bail, Result};
+use std::fmt::Write;
+
+/// Explains how the item at `path` is exported and referred to, and why it is or isn't reported,
+/// for `cargo purge why`
+pub fn explain(report: &Report, config: &PurgeConfig, path: &str) -> Result<String> {
+    let path = path.split("::").map(str::to_string).collect::<Vec<_>>();
+    let export = report.exports.get(&path);
+    let graph = &report.graph;
+
+    if export.is_none() && !graph.references.contains_key(&path) {
+        bail!(
+            "`{}` is not an item of the analyzed packages",
+            path.join("::")
+        );
+    }
+
+    let mut f = String::new();
+    let references = references_to(&report.references, &path);
+    let (module, _) = path.split_at(path.len() - 1);
+
+    // Glob imports of the item's module, or of a module re-exporting everything from it, refer
+    // to it without naming it
+    let mut glob_modules = vec![module];
+    glob_modules.extend(
+        graph
+            .glob_re_exports
+            .iter()
+            .filter(|(_, target)| target == module)
+            .map(|(re_exporting_module, _)| re_exporting_module.as_slice()),
+    );
+
+    let glob_imports = report
+        .references
+        .iter()
+        .filter(|reference| reference.glob && glob_modules.contains(&reference.path.as_slice()))
+        .collect::<Vec<_>>();
+
+    match export {
+        Some(export) => {
+            writeln!(f, "{} {}", export.kind, path.join("::"))?;
+            writeln!(
+                f,
+                "Defined at {}:{}:{}",
+                export.file.display(),
+                export.line,
+                export.column
+            )?;
+
+            let modules = (2..path.len())
+                .map(|length| &path[..length])
+                .filter(|module| {
+                    report
+                        .exports
+                        .get(*module)
+                        .is_some_and(|export| export.kind == ItemKind::Mod)
+                })
+                .map(|module| format!("`pub mod {}`", module.join("::")))
+                .collect::<Vec<_>>();
+
+            if export.visibility != "pub" {
+                writeln!(
+                    f,
+                    "Only visible inside its crate, as `{}`",
+                    export.visibility
+                )?;
+            } else if modules.is_empty() {
+                writeln!(f, "Exported from the crate root")?;
+            } else {
+                writeln!(f, "Exported through {}", modules.join(", "))?;
+            }
+        }
+        None => {
+            writeln!(f, "{}", path.join("::"))?;
+            writeln!(
+                f,
+                "Not exported: it is private, inside a private module, or in a package left out \
+                 of the report"
+            )?;
+        }
+    }
+
+    if let Some(target) = graph.re_exports.get(&path) {
+        writeln!(f, "Re-exports {}", target.join("::"))?;
+    }
+
+    for (alias, target) in &graph.re_exports {
+        if *target == path {
+            writeln!(f, "Re-exported as {}", alias.join("::"))?;
+        }
+    }
+
+    for (re_exporting_module, target) in &graph.glob_re_exports {
+        if target == module {
+            writeln!(
+                f,
+                "Re-exported by `pub use {}::*` in {}",
+                target.join("::"),
+                re_exporting_module.join("::")
+            )?;
+        }
+    }
+
+    writeln!(f)?;
+
+    if references.is_empty() {
+        writeln!(f, "No references")?;
+    } else {
+        writeln!(f, "References:")?;
+        write_references(&mut f, &references)?;
+    }
+
+    if !glob_imports.is_empty() {
+        writeln!(f)?;
+        writeln!(f, "Glob imports:")?;
+        write_references(&mut f, &glob_imports)?;
+    }
+
+    // Items whose code refers to this one, and whether anything reaches them
+    let production = graph.reachable(&graph.roots);
+    let all = graph.reachable(&graph.roots.union(&graph.test_roots).cloned().collect());
+
+    let referrers = graph
+        .references
+        .iter()
+        .filter(|(item, paths)| {
+            **item != path
+                && paths
+                    .iter()
+                    .any(|referenced| graph.resolve(referenced, 0).contains(&path))
+        })
+        .map(|(item, _)| item)
+        .filter(|item| !graph.modules.contains(*item))
+        .collect::<Vec<_>>();
+
+    if !referrers.is_empty() {
+        writeln!(f)?;
+        writeln!(f, "Referred to by:")?;
+
+        for item in &referrers {
+            let reach = if production.contains(*item) {
+                "reachable"
+            } else if all.contains(*item) {
+                "only reachable from tests"
+            } else {
+                "not reachable from any root"
+            };
+
+            writeln!(f, "    {} ({})", item.join("::"), reach)?;
+        }
+    }
+
+    let category = report
+        .findings()
+        .into_iter()
+        .find(|finding| finding.path == path)
+        .map(|finding| finding.category);
+
+    let named_by = report.named_in_public_signatures().remove(&path);
+
+    writeln!(f)?;
+
+    let verdict = match category {
+        Some(Category::Unused) if named_by.is_some() => {
+            "Reported as unused, but the signature of a public item names it, so it has to stay \
+             `pub` as long as that item does"
+        }
+        Some(Category::Unused) if config.reachability && !referrers.is_empty() => {
+            "Reported as unused: everything referring to it is dead code that no root reaches"
+        }
+        Some(Category::Unused)
+            if !glob_imports.is_empty() && config.glob_mode == GlobMode::Precise =>
+        {
+            "Reported as unused: its module is only glob imported, and with precise globs an \
+             import only counts the names the importing module refers to"
+        }
+        Some(Category::Unused)
+            if !config.reachability
+                && referrers.iter().all(|item| item[0] == path[0])
+                && !referrers.is_empty() =>
+        {
+            "Reported as unused: only its own crate refers to it, which doesn't need it exported"
+        }
+        Some(Category::Unused) if export.is_some_and(|export| export.used_locally) => {
+            "Reported as unused: only its own module refers to it, which doesn't need it exported"
+        }
+        Some(Category::Unused) if references.is_empty() => {
+            "Reported as unused: nothing refers to it"
+        }
+        Some(Category::Unused) => {
+            "Reported as unused: none of its references lead to it from a root"
+        }
+        Some(Category::TestOnly) if config.reachability => {
+            "Reported as only used in tests: only tests, examples and benches reach it"
+        }
+        Some(Category::TestOnly) => {
+            "Reported as only used in tests: every reference is in tests, examples, benches or \
+             `#[cfg(test)]` code"
+        }
+        Some(Category::OverExposed) => {
+            "Reported as over-exposed: only its own crate refers to it, so it doesn't need to be \
+             `pub`"
+        }
+        None if export.is_none() => "Not reported, as only exports are",
+        None if !references.is_empty() => "Not reported, as it is used",
+        None if !glob_imports.is_empty() => {
+            "Not reported: its module is glob imported, which counts everything in it as used"
+        }
+        None => {
+            "Not reported, although nothing refers to it by name: it is suppressed, ignored in \
+             the configuration, or used through a re-export"
+        }
+    };
+
+    writeln!(f, "{}", verdict)?;
+
+    if let Some(item) = named_by {
+        writeln!(f, "Named in the signature of {}", item.join("::"))?;
+    }
+
+    if let Some(narrowing) = report.suggested_visibility.get(&path) {
+        writeln!(f, "Its visibility can be narrowed to {}", narrowing)?;
+    }
+
+    Ok(f)
+}
+
+fn write_references(f: &mut String, references: &[&Reference]) -> std::fmt::Result {
+    for reference in references {
+        write!(
+            f,
+            "    {}:{}:{} {}",
+            reference.file.display(),
+            reference.line,
+            reference.column,
+            reference.package
+        )?;
+
+        if reference.test {
+            write!(f, " (test)")?;
+        }
+
+        writeln!(f)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,12 @@
+extern crate legacy;
+
+use text_utils::shout;
+
+#[derive(macros::Describe)]
+struct Greeting;
+
+fn main() {
+    logger::log!("starting");
+    println!("{}", shout("hello"));
+    legacy::run();
+}
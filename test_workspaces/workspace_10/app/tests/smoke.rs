@@ -0,0 +1,4 @@
+#[test]
+fn it_starts() {
+    kit::setup();
+}
@@ -0,0 +1,6 @@
+#[macro_export]
+macro_rules! log {
+    ($message:expr) => {
+        eprintln!("{}", $message)
+    };
+}
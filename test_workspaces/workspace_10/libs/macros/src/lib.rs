@@ -0,0 +1,6 @@
+use proc_macro::TokenStream;
+
+#[proc_macro_derive(Describe)]
+pub fn describe(_input: TokenStream) -> TokenStream {
+    TokenStream::new()
+}
@@ -0,0 +1,14 @@
+use core_lib::Greet;
+
+fn main() {
+    core_lib::used_by_main();
+    core_lib::re_exported();
+
+    let config = core_lib::Config::new();
+    println!("{}", config.greet());
+}
+
+#[allow(dead_code)]
+fn never_called() {
+    core_lib::used_by_dead_code();
+}
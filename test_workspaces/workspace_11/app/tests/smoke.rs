@@ -0,0 +1,4 @@
+#[test]
+fn it_runs() {
+    core_lib::used_by_tests();
+}
@@ -0,0 +1 @@
+pub fn re_exported() {}
@@ -0,0 +1,50 @@
+mod inner;
+
+pub use inner::re_exported;
+
+pub fn used_by_main() {}
+
+pub fn used_by_dead_code() {}
+
+pub fn chain_start() {
+    chain_end();
+}
+
+pub fn chain_end() {
+    chain_start();
+}
+
+pub fn used_by_tests() {}
+
+pub fn called_from_c() {}
+
+#[no_mangle]
+pub extern "C" fn plugin_entry() {
+    called_from_c();
+}
+
+pub struct Config;
+
+impl Config {
+    pub fn new() -> Self {
+        build_config()
+    }
+}
+
+pub fn build_config() -> Config {
+    Config
+}
+
+pub trait Greet {
+    fn greet(&self) -> String;
+}
+
+impl Greet for Config {
+    fn greet(&self) -> String {
+        greeting()
+    }
+}
+
+pub fn greeting() -> String {
+    "hello".to_string()
+}
@@ -0,0 +1,7 @@
+use exposed::ReExported;
+
+fn main() {
+    let _ = ReExported;
+    let total = exposed::run();
+    println!("{}", total);
+}
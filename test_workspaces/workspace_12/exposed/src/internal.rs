@@ -0,0 +1,19 @@
+#[derive(Default)]
+pub(crate) struct Settings {
+    pub(crate) level: u32,
+}
+
+pub(crate) fn unused_restricted() {}
+
+pub(crate) fn used_by_tests() -> u32 {
+    4
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn it_counts() {
+        let count = super::used_by_tests();
+        assert_eq!(count, 4);
+    }
+}
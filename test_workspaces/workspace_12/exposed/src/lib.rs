@@ -0,0 +1,12 @@
+mod internal;
+pub mod outer;
+
+pub use outer::inner::ReExported;
+
+pub fn run() -> u32 {
+    outer::summarize() + crate::outer::inner::crate_wide() + outer::for_root()
+}
+
+pub fn root_helper() -> u32 {
+    internal::Settings::default().level
+}
@@ -0,0 +1,5 @@
+use super::own_module_only;
+
+pub fn compute() -> u32 {
+    own_module_only()
+}
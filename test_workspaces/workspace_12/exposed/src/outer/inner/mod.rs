@@ -0,0 +1,17 @@
+mod detail;
+
+pub struct ReExported;
+
+pub fn for_parent() -> u32 {
+    detail::compute()
+}
+
+pub fn crate_wide() -> u32 {
+    2
+}
+
+pub fn own_module_only() -> u32 {
+    3
+}
+
+pub(in crate::outer) fn scoped_unused() {}
@@ -0,0 +1,13 @@
+pub mod inner;
+
+pub fn summarize() -> u32 {
+    inner::for_parent() + inner::crate_wide() + crate::root_helper() + counted()
+}
+
+pub(super) fn for_root() -> u32 {
+    0
+}
+
+pub(self) fn counted() -> u32 {
+    5
+}
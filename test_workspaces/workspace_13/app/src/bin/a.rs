@@ -0,0 +1,5 @@
+use lib1::*;
+
+fn main() {
+    used_through_glob();
+}
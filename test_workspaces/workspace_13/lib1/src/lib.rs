@@ -0,0 +1,3 @@
+pub fn used() {}
+
+pub fn used_through_glob() {}
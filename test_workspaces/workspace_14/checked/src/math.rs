@@ -0,0 +1,15 @@
+pub fn double(value: u32) -> u32 {
+    value * 2
+}
+
+pub fn never_used() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_doubles() {
+        assert_eq!(double(2), 4);
+    }
+}
@@ -0,0 +1,3 @@
+fn main() {
+    cyclic::c::b::f();
+}
@@ -0,0 +1,12 @@
+pub mod a {
+    pub mod b {
+        pub use crate::*;
+    }
+}
+
+pub use a as c;
+pub use a::b::*;
+
+pub fn f() {}
+
+pub fn unused() {}
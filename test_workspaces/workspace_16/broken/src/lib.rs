@@ -0,0 +1,3 @@
+pub mod parsed;
+
+pub fn fine() {}
@@ -0,0 +1,3 @@
+pub fn fine_too() {}
+
+pub struct {}
@@ -0,0 +1,4 @@
+fn main() {
+    let absolute = unsafe { ffi::abs(-1) };
+    ffi::absolute(absolute.into());
+}
@@ -0,0 +1,14 @@
+extern "C" {
+    pub fn abs(input: i32) -> i32;
+
+    pub fn labs(input: i64) -> i64;
+
+    /// Never read
+    pub static timezone: i64;
+
+    fn llabs(input: i64) -> i64;
+}
+
+pub fn absolute(input: i64) -> i64 {
+    unsafe { llabs(input) }
+}
@@ -0,0 +1,4 @@
+fn main() {
+    let _ = macros::used_macro!();
+    macros::generated();
+}
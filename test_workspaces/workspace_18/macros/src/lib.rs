@@ -0,0 +1,26 @@
+mod nested {
+    #[macro_export]
+    macro_rules! nested_unused {
+        () => {};
+    }
+}
+
+#[macro_export]
+macro_rules! used_macro {
+    () => {
+        0
+    };
+}
+
+#[macro_export]
+macro_rules! unused_macro {
+    () => {};
+}
+
+macro_rules! local_helper {
+    ($name:ident) => {
+        pub fn $name() {}
+    };
+}
+
+local_helper!(generated);
@@ -0,0 +1,3 @@
+pub fn only_bin_crate_path() {}
+
+pub fn used() {}
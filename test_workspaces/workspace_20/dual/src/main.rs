@@ -0,0 +1,6 @@
+fn only_bin_crate_path() {}
+
+fn main() {
+    crate::only_bin_crate_path();
+    dual::used();
+}
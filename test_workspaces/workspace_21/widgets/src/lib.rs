@@ -0,0 +1,11 @@
+pub struct Gear;
+
+pub struct Widget {
+    pub gear: Gear,
+}
+
+pub fn make() -> Widget {
+    Widget { gear: Gear }
+}
+
+pub fn unused() {}
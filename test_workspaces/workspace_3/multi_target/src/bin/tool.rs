@@ -0,0 +1,5 @@
+use multi_target::used_by_tool;
+
+fn main() {
+    used_by_tool();
+}
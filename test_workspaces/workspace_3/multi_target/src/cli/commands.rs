@@ -0,0 +1,3 @@
+pub fn run() {
+    multi_target::helpers::used_by_cli_module();
+}
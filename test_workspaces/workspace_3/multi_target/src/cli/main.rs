@@ -0,0 +1,6 @@
+mod commands;
+
+fn main() {
+    multi_target::used_by_cli();
+    commands::run();
+}
@@ -0,0 +1,23 @@
+#[path = "platform/unix.rs"]
+mod platform;
+pub mod helpers;
+
+pub fn used_by_cli() {
+    println!("Hello, world!");
+}
+
+pub fn used_by_tool() {
+    println!("Hello, world!");
+}
+
+pub fn unused() {
+    println!("Hello, world!");
+}
+
+pub fn used_by_tests() {
+    println!("Hello, world!");
+}
+
+pub fn used_by_example() {
+    println!("Hello, world!");
+}
@@ -0,0 +1,3 @@
+pub fn used_by_cli_module() {
+    println!("Hello, world!");
+}
@@ -0,0 +1,2 @@
+// Left behind when the library root moved to `core.rs`
+pub fn stale() {}
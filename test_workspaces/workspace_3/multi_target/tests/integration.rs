@@ -0,0 +1,4 @@
+#[test]
+fn it_says_hello() {
+    multi_target::used_by_tests();
+}
@@ -0,0 +1,11 @@
+use f::api::used_directly as direct;
+use facade as f;
+use facade::api::Widget;
+use facade::prelude::*;
+use facade::Thing;
+
+fn main() {
+    f::renamed();
+    direct();
+    glob_used();
+}
@@ -0,0 +1,9 @@
+pub struct Widget;
+
+pub fn used_directly() {
+    println!("Hello, world!");
+}
+
+pub fn unused_function() {
+    println!("Hello, world!");
+}
@@ -0,0 +1,5 @@
+pub struct Thing;
+
+pub fn hidden() {
+    println!("Hello, world!");
+}
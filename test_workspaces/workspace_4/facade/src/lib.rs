@@ -0,0 +1,13 @@
+mod inner;
+mod relative;
+pub mod api;
+pub mod tools;
+
+pub mod prelude {
+    pub use crate::tools::*;
+}
+
+pub use crate::api::unused_function;
+pub use inner::hidden as renamed;
+pub use inner::Thing;
+pub use self::api::Widget;
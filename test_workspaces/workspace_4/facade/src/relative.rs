@@ -0,0 +1,15 @@
+use self::nested::helper;
+use super::api::Widget;
+use crate::inner::Thing;
+
+pub fn run() {
+    super::tools::glob_used();
+    crate::api::used_directly();
+    self::nested::helper();
+}
+
+mod nested {
+    pub fn helper() {
+        super::super::inner::hidden();
+    }
+}
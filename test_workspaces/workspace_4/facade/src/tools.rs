@@ -0,0 +1,7 @@
+pub fn glob_used() {
+    println!("Hello, world!");
+}
+
+pub fn glob_unused() {
+    println!("Hello, world!");
+}
@@ -0,0 +1,33 @@
+struct Holder {
+    field: shapes::Field,
+}
+
+struct Wrapper(shapes::Square);
+
+impl shapes::Area for Holder {}
+
+fn draw(circle: shapes::Circle) -> Option<shapes::Generic> {
+    None
+}
+
+fn bounded<T: shapes::Bound>(value: T) {}
+
+fn constrained<T>(value: T)
+where
+    T: shapes::Constraint,
+{
+}
+
+fn unpack(value: Wrapper, colour: Colour) {
+    let shapes::Pattern(inner) = value.0;
+
+    match colour {
+        shapes::Colour::Red => {}
+    }
+}
+
+fn main() {
+    let radius = 1.0 as shapes::Radius;
+    let shapes = Vec::<shapes::Turbofish>::new();
+    let literal = shapes::Literal { sides: 4 };
+}
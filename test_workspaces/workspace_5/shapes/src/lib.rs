@@ -0,0 +1,20 @@
+pub struct Circle;
+pub struct Field;
+pub struct Generic;
+pub struct Square;
+pub struct Turbofish;
+pub struct Pattern(pub u8);
+pub struct Literal {
+    pub sides: u8,
+}
+pub struct Unused;
+
+pub enum Colour {
+    Red,
+}
+
+pub trait Area {}
+pub trait Bound {}
+pub trait Constraint {}
+
+pub type Radius = f64;
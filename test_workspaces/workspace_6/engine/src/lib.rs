@@ -0,0 +1,9 @@
+pub mod generated;
+
+pub fn used() {}
+
+pub fn unused() {}
+
+pub fn ignored_by_pattern() {}
+
+pub fn legacy() {}
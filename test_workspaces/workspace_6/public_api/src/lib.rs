@@ -0,0 +1 @@
+pub fn only_used_by_downstream_crates() {}
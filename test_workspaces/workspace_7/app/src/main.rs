@@ -0,0 +1,3 @@
+fn main() {
+    ffi::kept_but_used();
+}
@@ -0,0 +1,21 @@
+pub mod plugins;
+
+// Looked up by name from the host application
+// purge:keep
+#[no_mangle]
+pub extern "C" fn exported_symbol() {}
+
+pub fn kept_by_trailing_comment() {} // purge:keep
+
+#[cfg_attr(purge, keep)]
+pub fn kept_by_attribute() {}
+
+#[cfg_attr(purge, keep)]
+pub mod reflection {
+    pub fn registered_handler() {}
+}
+
+#[cfg_attr(purge, keep)]
+pub fn kept_but_used() {}
+
+pub fn not_kept() {}
@@ -0,0 +1,3 @@
+#![cfg_attr(purge, keep)]
+
+pub fn load() {}
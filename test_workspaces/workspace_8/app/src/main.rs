@@ -0,0 +1,4 @@
+fn main() {
+    widgets::used();
+    let _square = widgets::shapes::Square;
+}
@@ -0,0 +1,11 @@
+pub mod shapes;
+
+pub fn used() {
+    helper();
+}
+
+pub fn helper() {}
+
+pub fn unused() {}
+
+pub(crate) fn already_narrow() {}
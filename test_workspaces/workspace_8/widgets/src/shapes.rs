@@ -0,0 +1,3 @@
+pub struct Square;
+
+pub const SIDES: usize = 4;
@@ -0,0 +1,6 @@
+fn main() {
+    widgets::used();
+
+    // Macro arguments aren't analyzed, so this use of `LIMIT` goes unnoticed
+    println!("{}", widgets::LIMIT);
+}
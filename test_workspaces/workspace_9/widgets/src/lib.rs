@@ -0,0 +1,11 @@
+pub mod legacy;
+
+pub fn used() {}
+
+/// Nothing calls this
+#[inline]
+pub fn unused() {}
+
+pub const LIMIT: u32 = 10;
+
+pub struct Gadget;